@@ -7,7 +7,7 @@
 
 use std::sync::Arc;
 
-use node_polkadex_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_polkadex_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
 
 pub use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
@@ -33,10 +33,12 @@ pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc
 		C: Send + Sync + 'static,
 		C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 		C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+		C::Api: polkadex_ocex_rpc::OcexRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
 		C::Api: BlockBuilder<Block>,
 		P: TransactionPool + 'static,
 {
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use polkadex_ocex_rpc::{Ocex, OcexApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
@@ -56,6 +58,8 @@ pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc
 		client.clone(),
 	)));
 
+	io.extend_with(OcexApi::to_delegate(Ocex::new(client.clone())));
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
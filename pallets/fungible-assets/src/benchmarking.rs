@@ -0,0 +1,150 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Benchmarks for the fungible assets pallet
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use polkadex_primitives::assets::AssetId;
+use sp_core::H160;
+
+const SEED: u32 = 0;
+
+fn asset<T: Config>() -> T::CurrencyId
+where
+    T::CurrencyId: From<AssetId>,
+{
+    AssetId::CHAINSAFE(H160::from_low_u64_be(1)).into()
+}
+
+/// Creates an asset which `creator` can mint and burn
+fn create_asset<T: Config>(creator: &T::AccountId) -> Result<T::CurrencyId, &'static str>
+where
+    T::CurrencyId: From<AssetId>,
+{
+    let asset_id = asset::<T>();
+    Module::<T>::create_token(
+        RawOrigin::Signed(creator.clone()).into(),
+        asset_id,
+        1_000_000u32.into(),
+        Some(creator.clone()),
+        Some(creator.clone()),
+        1u32.into(),
+    )?;
+    Ok(asset_id)
+}
+
+benchmarks! {
+    where_clause { where T::CurrencyId: From<AssetId> }
+
+    create_token {
+        let caller: T::AccountId = whitelisted_caller();
+        let fee: T::Balance = 1_000u32.into();
+        <FixedPDXAmount<T>>::put(fee);
+        T::NativeCurrency::deposit(&caller, 1_000_000u32.into())?;
+        let asset_id = asset::<T>();
+    }: _(RawOrigin::Signed(caller.clone()), asset_id, 1_000_000u32.into(), Some(caller.clone()), Some(caller.clone()), 1u32.into())
+    verify {
+        assert_eq!(Module::<T>::get_assetinfo(asset_id).creator, caller);
+    }
+
+    set_vesting_info {
+        let caller: T::AccountId = whitelisted_caller();
+        let beneficiary: T::AccountId = account("beneficiary", 0, SEED);
+        let asset_id = create_asset::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), 1_000u32.into(), asset_id, 1u32.into(), beneficiary.clone())
+    verify {
+        assert_eq!(<InfoVesting<T>>::iter_prefix((beneficiary, asset_id)).count(), 1);
+    }
+
+    claim_vesting {
+        let caller: T::AccountId = whitelisted_caller();
+        let asset_id = create_asset::<T>(&caller)?;
+        Module::<T>::set_vesting_info(
+            RawOrigin::Signed(caller.clone()).into(),
+            1_000u32.into(),
+            asset_id,
+            1u32.into(),
+            caller.clone(),
+        )?;
+        let (identifier, _) = <InfoVesting<T>>::iter_prefix((caller.clone(), asset_id))
+            .next()
+            .ok_or("vesting info not stored")?;
+        <system::Module<T>>::set_block_number(10u32.into());
+    }: _(RawOrigin::Signed(caller.clone()), identifier, asset_id)
+    verify {
+        assert!(Module::<T>::get_vestinginfo((caller, asset_id), identifier).amount < T::Balance::from(1_000u32));
+    }
+
+    set_metadata_fungible {
+        // Total length of the three fields, each at most 1024 bytes
+        let n in 0 .. 3 * 1024;
+        let caller: T::AccountId = whitelisted_caller();
+        let asset_id = create_asset::<T>(&caller)?;
+        let metadata = AssetMetadata {
+            name: vec![0u8; (n / 3) as usize],
+            website: vec![0u8; (n / 3) as usize],
+            team: vec![0u8; (n - 2 * (n / 3)) as usize],
+        };
+    }: _(RawOrigin::Signed(caller), asset_id, metadata)
+    verify {
+        assert!(Module::<T>::get_assetinfo(asset_id).metadata.is_some());
+    }
+
+    mint_fungible {
+        let caller: T::AccountId = whitelisted_caller();
+        let recipient: T::AccountId = account("recipient", 0, SEED);
+        let asset_id = create_asset::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), recipient.clone(), asset_id, 1_000u32.into())
+    verify {
+        assert_eq!(orml_tokens::Accounts::<T>::get(recipient, asset_id).free, T::Balance::from(1_000u32));
+    }
+
+    burn_fungible {
+        let caller: T::AccountId = whitelisted_caller();
+        let asset_id = create_asset::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller.clone()), asset_id, 1_000u32.into())
+    verify {
+        assert_eq!(orml_tokens::Accounts::<T>::get(caller, asset_id).free, T::Balance::from(999_000u32));
+    }
+
+    attest_token {
+        let caller: T::AccountId = whitelisted_caller();
+        let asset_id = create_asset::<T>(&caller)?;
+        let origin = T::GovernanceOrigin::successful_origin();
+        let call = Call::<T>::attest_token(asset_id);
+    }: { call.dispatch_bypass_filter(origin)? }
+    verify {
+        assert!(Module::<T>::get_assetinfo(asset_id).is_verified);
+    }
+
+    modify_token_deposit_amount {
+        let origin = T::GovernanceOrigin::successful_origin();
+        let call = Call::<T>::modify_token_deposit_amount(1_000u32.into());
+    }: { call.dispatch_bypass_filter(origin)? }
+    verify {
+        assert_eq!(Module::<T>::get_amount(), T::Balance::from(1_000u32));
+    }
+}
+
+impl_benchmark_test_suite!(Module, crate::mock::new_tester(), crate::mock::Test);
@@ -37,6 +37,12 @@ use orml_traits::{
     BasicCurrency, BasicCurrencyExtended, BasicLockableCurrency, BasicReservableCurrency,
 };
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
 #[cfg(test)]
 mod mock;
 
@@ -53,6 +59,9 @@ pub trait Config: system::Config + orml_tokens::Config {
     type NativeCurrency: BasicCurrencyExtended<Self::AccountId, Balance = BalanceOf<Self>>
     + BasicLockableCurrency<Self::AccountId, Balance = BalanceOf<Self>>
     + BasicReservableCurrency<Self::AccountId, Balance = BalanceOf<Self>>;
+
+    /// Weight information for the extrinsics of this pallet
+    type WeightInfo: WeightInfo;
 }
 
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
@@ -178,7 +187,7 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Create new token.
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::create_token()]
         pub fn create_token(origin,
                         asset_id: T::CurrencyId,
                         max_supply: T::Balance,
@@ -201,7 +210,7 @@ decl_module! {
         }
 
         /// Vesting
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::set_vesting_info()]
         pub fn set_vesting_info(origin, amount: T::Balance, asset_id: T::CurrencyId, rate: T::Balance, account: T::AccountId) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             let asset_info: AssetInfo<T> = <InfoAsset<T>>::get(asset_id);
@@ -215,7 +224,7 @@ decl_module! {
         }
 
         /// Claim
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::claim_vesting()]
         pub fn claim_vesting(origin, identifier: T::Hash, asset_id: T::CurrencyId) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             let current_block_no = <system::Module<T>>::block_number();
@@ -233,7 +242,9 @@ decl_module! {
         }
 
         /// Set Metadata
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::set_metadata_fungible(
+            (metadata.name.len() + metadata.website.len() + metadata.team.len()) as u32
+        )]
         pub fn set_metadata_fungible(origin, asset_id: T::CurrencyId, metadata: AssetMetadata) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             ensure!(<InfoAsset<T>>::contains_key(&asset_id), <Error<T>>::AssetIdNotExists);
@@ -248,7 +259,7 @@ decl_module! {
         }
 
         /// Minting
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::mint_fungible()]
         pub fn mint_fungible(origin,to: T::AccountId, asset_id: T::CurrencyId, amount: T::Balance) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             Self::mint_token(&who, &to,asset_id, amount)?;
@@ -257,7 +268,7 @@ decl_module! {
         }
 
         /// Burn
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::burn_fungible()]
         pub fn burn_fungible(origin, asset_id: T::CurrencyId, amount: T::Balance) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             Self::burn_token(&who,asset_id, amount)?;
@@ -266,7 +277,7 @@ decl_module! {
         }
 
         /// Attest Token
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::attest_token()]
         pub fn attest_token(origin, asset_id: T::CurrencyId) -> DispatchResult {
             T::GovernanceOrigin::ensure_origin(origin)?;
             ensure!(<InfoAsset<T>>::contains_key(&asset_id), <Error<T>>::AssetIdNotExists);
@@ -278,7 +289,7 @@ decl_module! {
         }
 
         /// Modify Token Registration
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::modify_token_deposit_amount()]
         pub fn modify_token_deposit_amount(origin, pdx_amount: T::Balance) -> DispatchResult {
             T::GovernanceOrigin::ensure_origin(origin)?;
             <FixedPDXAmount<T>>::put::<T::Balance>(pdx_amount);
@@ -122,6 +122,7 @@ impl Config for Test {
     type TreasuryAccountId = TresuryAccount;
     type GovernanceOrigin = EnsureSignedBy<Six, u64>;
     type NativeCurrency =AdaptedBasicCurrency;
+    type WeightInfo = ();
 }
 
 pub type AdaptedBasicCurrency = BasicCurrencyAdapter<Test, PalletBalances, i128, u128>;
@@ -0,0 +1,72 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the OCEX pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use polkadex_primitives::assets::AssetId;
+use polkadex_primitives::markets::{MarketInfo, TradingPair};
+use polkadex_primitives::ocex::{EnclaveStatus, OcexOperation, ReserveStatus, WithdrawalRequest};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait OcexApi<AccountId, Balance, BlockNumber, Hash> where
+        AccountId: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+        Hash: Codec,
+    {
+        /// Merkle root of the balance snapshot committed for `epoch`
+        fn balance_snapshot_root(epoch: u64) -> Option<Hash>;
+
+        /// Checks a balance inclusion proof against the snapshot committed for `epoch`
+        fn verify_balance_proof(
+            epoch: u64,
+            account: AccountId,
+            asset_id: AssetId,
+            balance: Balance,
+            proof: Vec<Hash>,
+        ) -> bool;
+
+        /// Trading rules and status of a market
+        fn market(market: TradingPair) -> Option<MarketInfo<Balance>>;
+
+        /// All listed markets
+        fn markets() -> Vec<(TradingPair, MarketInfo<Balance>)>;
+
+        /// Free balances of the custody account per asset
+        fn custody_balances() -> Vec<(AssetId, Balance)>;
+
+        /// Withdrawal requests of `account` which are not settled or rejected yet
+        fn pending_withdrawals(account: AccountId) -> Vec<(u64, WithdrawalRequest<AccountId, Balance, BlockNumber>)>;
+
+        /// Enclaves known to the registry
+        fn registered_enclaves() -> Vec<EnclaveStatus<AccountId>>;
+
+        /// Paused operations, for one asset or for all assets if the asset is `None`
+        fn paused_operations() -> Vec<(OcexOperation, Option<AssetId>)>;
+
+        /// Main account a trading key signs orders for, by the account of the key
+        fn trading_key_owner(trading_key: AccountId) -> Option<AccountId>;
+
+        /// Custody reserves against the liabilities reported by the enclave per asset
+        fn reserve_ratios() -> Vec<(AssetId, ReserveStatus<Balance>)>;
+    }
+}
@@ -0,0 +1,238 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! RPC interface for the OCEX pallet.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use polkadex_primitives::assets::AssetId;
+use polkadex_primitives::markets::{MarketInfo, TradingPair};
+use polkadex_primitives::ocex::{EnclaveStatus, OcexOperation, ReserveStatus, WithdrawalRequest};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use polkadex_ocex_runtime_api::OcexApi as OcexRuntimeApi;
+
+#[rpc]
+pub trait OcexApi<BlockHash, AccountId, Balance, BlockNumber, Hash> {
+    /// Merkle root of the balance snapshot committed for `epoch`
+    #[rpc(name = "ocex_balanceSnapshotRoot")]
+    fn balance_snapshot_root(&self, epoch: u64, at: Option<BlockHash>) -> Result<Option<Hash>>;
+
+    /// Checks a balance inclusion proof against the snapshot committed for `epoch`
+    #[rpc(name = "ocex_verifyBalanceProof")]
+    fn verify_balance_proof(
+        &self,
+        epoch: u64,
+        account: AccountId,
+        asset_id: AssetId,
+        balance: Balance,
+        proof: Vec<Hash>,
+        at: Option<BlockHash>,
+    ) -> Result<bool>;
+
+    /// Trading rules and status of a market
+    #[rpc(name = "ocex_market")]
+    fn market(
+        &self,
+        market: TradingPair,
+        at: Option<BlockHash>,
+    ) -> Result<Option<MarketInfo<Balance>>>;
+
+    /// All listed markets
+    #[rpc(name = "ocex_markets")]
+    fn markets(&self, at: Option<BlockHash>) -> Result<Vec<(TradingPair, MarketInfo<Balance>)>>;
+
+    /// Free balances of the custody account per asset
+    #[rpc(name = "ocex_custodyBalances")]
+    fn custody_balances(&self, at: Option<BlockHash>) -> Result<Vec<(AssetId, Balance)>>;
+
+    /// Withdrawal requests of `account` which are not settled or rejected yet
+    #[rpc(name = "ocex_pendingWithdrawals")]
+    fn pending_withdrawals(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(u64, WithdrawalRequest<AccountId, Balance, BlockNumber>)>>;
+
+    /// Enclaves known to the registry
+    #[rpc(name = "ocex_registeredEnclaves")]
+    fn registered_enclaves(&self, at: Option<BlockHash>) -> Result<Vec<EnclaveStatus<AccountId>>>;
+
+    /// Paused operations, for one asset or for all assets if the asset is `None`
+    #[rpc(name = "ocex_pausedOperations")]
+    fn paused_operations(
+        &self,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(OcexOperation, Option<AssetId>)>>;
+
+    /// Main account a trading key signs orders for, by the account of the key
+    #[rpc(name = "ocex_tradingKeyOwner")]
+    fn trading_key_owner(
+        &self,
+        trading_key: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Option<AccountId>>;
+
+    /// Custody reserves against the liabilities reported by the enclave per asset
+    #[rpc(name = "ocex_reserveRatios")]
+    fn reserve_ratios(&self, at: Option<BlockHash>) -> Result<Vec<(AssetId, ReserveStatus<Balance>)>>;
+}
+
+/// Error code returned when the runtime call fails
+const RUNTIME_ERROR: i64 = 1;
+
+/// Implements the OCEX RPC on top of the runtime API
+pub struct Ocex<C, Block> {
+    client: Arc<C>,
+    _marker: PhantomData<Block>,
+}
+
+impl<C, Block> Ocex<C, Block> {
+    pub fn new(client: Arc<C>) -> Self {
+        Ocex {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn runtime_error(err: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query OCEX state.".into(),
+        data: Some(format!("{:?}", err).into()),
+    }
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber, Hash>
+    OcexApi<<Block as BlockT>::Hash, AccountId, Balance, BlockNumber, Hash> for Ocex<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: OcexRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+    AccountId: Codec,
+    Balance: Codec,
+    BlockNumber: Codec,
+    Hash: Codec,
+{
+    fn balance_snapshot_root(
+        &self,
+        epoch: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Hash>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.balance_snapshot_root(&at, epoch).map_err(runtime_error)
+    }
+
+    fn verify_balance_proof(
+        &self,
+        epoch: u64,
+        account: AccountId,
+        asset_id: AssetId,
+        balance: Balance,
+        proof: Vec<Hash>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<bool> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.verify_balance_proof(&at, epoch, account, asset_id, balance, proof)
+            .map_err(runtime_error)
+    }
+
+    fn market(
+        &self,
+        market: TradingPair,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<MarketInfo<Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.market(&at, market).map_err(runtime_error)
+    }
+
+    fn markets(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(TradingPair, MarketInfo<Balance>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.markets(&at).map_err(runtime_error)
+    }
+
+    fn custody_balances(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(AssetId, Balance)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.custody_balances(&at).map_err(runtime_error)
+    }
+
+    fn pending_withdrawals(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(u64, WithdrawalRequest<AccountId, Balance, BlockNumber>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.pending_withdrawals(&at, account).map_err(runtime_error)
+    }
+
+    fn registered_enclaves(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<EnclaveStatus<AccountId>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.registered_enclaves(&at).map_err(runtime_error)
+    }
+
+    fn paused_operations(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(OcexOperation, Option<AssetId>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.paused_operations(&at).map_err(runtime_error)
+    }
+
+    fn trading_key_owner(
+        &self,
+        trading_key: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<AccountId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.trading_key_owner(&at, trading_key).map_err(runtime_error)
+    }
+
+    fn reserve_ratios(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(AssetId, ReserveStatus<Balance>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.reserve_ratios(&at).map_err(runtime_error)
+    }
+}
@@ -0,0 +1,101 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sources of the enclaves OCEX accepts releases and trades from
+
+use super::*;
+
+use frame_support::{IterableStorageMap, StorageValue};
+use sp_std::marker::PhantomData;
+
+/// Knows which accounts are enclaves and which build they run
+///
+/// Whether a build may act is still decided by the OCEX allowlist.
+pub trait EnclaveAuthority<AccountId> {
+    /// MRENCLAVE of the build `enclave` runs, or `None` if it is not an enclave
+    fn mr_enclave(enclave: &AccountId) -> Option<MrEnclave>;
+
+    /// All known enclaves with the MRENCLAVE of their build
+    fn enclaves() -> Vec<(AccountId, MrEnclave)>;
+
+    /// Whether enclave keys set through `add_static_enclave` are enclaves
+    fn accepts_static_keys() -> bool;
+
+    /// Makes `enclave` known as running `mr_enclave`
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_enclave(enclave: &AccountId, mr_enclave: MrEnclave);
+}
+
+/// Enclaves which attested through `pallet_substratee_registry`
+pub struct RegistryEnclaves<T>(PhantomData<T>);
+
+impl<T: pallet_substratee_registry::Config> EnclaveAuthority<T::AccountId> for RegistryEnclaves<T> {
+    fn mr_enclave(enclave: &T::AccountId) -> Option<MrEnclave> {
+        if !pallet_substratee_registry::EnclaveIndex::<T>::contains_key(enclave) {
+            return None;
+        }
+        let index = pallet_substratee_registry::EnclaveIndex::<T>::get(enclave);
+        Some(pallet_substratee_registry::EnclaveRegistry::<T>::get(index).mr_enclave)
+    }
+
+    fn enclaves() -> Vec<(T::AccountId, MrEnclave)> {
+        (1..=pallet_substratee_registry::EnclaveCount::get())
+            .map(pallet_substratee_registry::EnclaveRegistry::<T>::get)
+            .map(|enclave| (enclave.pubkey, enclave.mr_enclave))
+            .collect()
+    }
+
+    fn accepts_static_keys() -> bool {
+        false
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_enclave(enclave: &T::AccountId, mr_enclave: MrEnclave) {
+        let index = pallet_substratee_registry::EnclaveCount::get() + 1;
+        pallet_substratee_registry::EnclaveRegistry::<T>::insert(
+            index,
+            pallet_substratee_registry::Enclave::new(enclave.clone(), mr_enclave, 0, Vec::new()),
+        );
+        pallet_substratee_registry::EnclaveIndex::<T>::insert(enclave, index);
+        pallet_substratee_registry::EnclaveCount::put(index);
+    }
+}
+
+/// Enclave keys set by governance through `add_static_enclave`
+///
+/// Lets dev chains and tests run the deposit and release flow without SGX.
+pub struct StaticEnclaves<T>(PhantomData<T>);
+
+impl<T: Config> EnclaveAuthority<T::AccountId> for StaticEnclaves<T> {
+    fn mr_enclave(enclave: &T::AccountId) -> Option<MrEnclave> {
+        Module::<T>::static_enclave(enclave)
+    }
+
+    fn enclaves() -> Vec<(T::AccountId, MrEnclave)> {
+        <StaticEnclaveKeys<T>>::iter().collect()
+    }
+
+    fn accepts_static_keys() -> bool {
+        true
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_enclave(enclave: &T::AccountId, mr_enclave: MrEnclave) {
+        <StaticEnclaveKeys<T>>::insert(enclave, mr_enclave);
+    }
+}
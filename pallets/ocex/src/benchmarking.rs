@@ -0,0 +1,625 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Benchmarks for the OCEX pallet
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use sp_core::sr25519;
+
+const SEED: u32 = 0;
+const ASSET: AssetId = AssetId::POLKADEX;
+const MR_ENCLAVE: MrEnclave = [1u8; 32];
+const SUCCESSOR: MrEnclave = [2u8; 32];
+
+fn balance<T: Config>(value: u32) -> T::Balance {
+    value.into()
+}
+
+fn governance<T: Config>() -> T::Origin {
+    <T as Config>::GovernanceOrigin::successful_origin()
+}
+
+fn allow_mrenclave<T: Config>(mr_enclave: MrEnclave) {
+    <AllowedMrEnclaves<T>>::insert(
+        mr_enclave,
+        MrEnclaveInfo {
+            allowed_at: Zero::zero(),
+            expires_at: None,
+        },
+    );
+}
+
+/// Registers a live enclave running `mr_enclave`
+fn add_enclave<T: Config>(index: u32, mr_enclave: MrEnclave) -> T::AccountId {
+    let enclave: T::AccountId = account("enclave", index, SEED);
+    T::EnclaveAuthority::register_enclave(&enclave, mr_enclave);
+    allow_mrenclave::<T>(mr_enclave);
+    <LiveEnclaves<T>>::append(enclave.clone());
+    enclave
+}
+
+/// Funds the custody account with `value` of the benchmarked asset
+fn fund_custody<T: Config>(value: u32) -> DispatchResult {
+    <T as Config>::Currency::deposit(ASSET, &Module::<T>::get_account(), balance::<T>(value))
+}
+
+/// Requests a withdrawal of `value` for a new user
+fn request_withdrawal<T: Config>(index: u32, value: u32) -> Result<WithdrawalId, &'static str> {
+    let user: T::AccountId = account("user", index, SEED);
+    let withdrawal_id = Module::<T>::next_withdrawal_id();
+    Module::<T>::withdraw(
+        RawOrigin::Signed(user.clone()).into(),
+        ASSET,
+        user,
+        balance::<T>(value),
+    )?;
+    Ok(withdrawal_id)
+}
+
+/// Limits the outflow without holding back the benchmarked releases
+fn set_outflow_limit<T: Config>() {
+    <OutflowLimits<T>>::insert(
+        ASSET,
+        OutflowLimit {
+            max_amount: balance::<T>(u32::max_value()),
+            period: 100u32.into(),
+        },
+    );
+}
+
+/// Releases a withdrawal into a challenge window of ten blocks
+fn delayed_release<T: Config>() -> Result<WithdrawalId, &'static str> {
+    let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+    fund_custody::<T>(1_000_000)?;
+    <ChallengeWindow<T>>::put(T::BlockNumber::from(10u32));
+    let withdrawal_id = request_withdrawal::<T>(0, 1_000)?;
+    Module::<T>::release(RawOrigin::Signed(enclave).into(), withdrawal_id, 1)?;
+    Ok(withdrawal_id)
+}
+
+/// Funds a watcher with the challenge bond
+fn watcher<T: Config>() -> Result<T::AccountId, &'static str> {
+    let watcher: T::AccountId = account("watcher", 0, SEED);
+    let funds = T::ChallengeBond::get().saturating_add(balance::<T>(1_000_000));
+    <T as Config>::Currency::deposit(AssetId::POLKADEX, &watcher, funds)?;
+    Ok(watcher)
+}
+
+/// Challenges `withdrawal_id` by a funded watcher
+fn challenge<T: Config>(withdrawal_id: WithdrawalId) -> DispatchResult {
+    let watcher = watcher::<T>()?;
+    Module::<T>::challenge_release(RawOrigin::Signed(watcher).into(), withdrawal_id)
+}
+
+fn open_market<T: Config>() -> TradingPair {
+    let market = TradingPair {
+        base: AssetId::POLKADEX,
+        quote: AssetId::DOT,
+    };
+    <Markets<T>>::insert(
+        market,
+        MarketInfo {
+            tick_size: balance::<T>(1),
+            lot_size: balance::<T>(1),
+            min_notional: balance::<T>(1),
+            status: MarketStatus::Open,
+        },
+    );
+    market
+}
+
+/// Proposes `key` for `main` and confirms it with the key
+fn register_key<T: Config>(main: &T::AccountId, key: T::TradingKey) -> DispatchResult {
+    let key_account = key.clone().into_account();
+    Module::<T>::register_trading_key(RawOrigin::Signed(main.clone()).into(), key)?;
+    Module::<T>::confirm_trading_key(RawOrigin::Signed(key_account).into(), main.clone())
+}
+
+/// Commits a snapshot whose root is the leaf of `account` so that an empty proof verifies
+fn commit_snapshot<T: Config>(epoch: SnapshotEpoch, account: &T::AccountId, value: u32) -> T::Hash {
+    let root = Module::<T>::balance_leaf(account, ASSET, balance::<T>(value));
+    <BalanceSnapshots<T>>::insert(epoch, root);
+    LatestSnapshotEpoch::put(epoch);
+    root
+}
+
+benchmarks! {
+    where_clause { where T::TradingKey: From<sr25519::Public> }
+
+    deposit {
+        let caller: T::AccountId = whitelisted_caller();
+        add_enclave::<T>(0, MR_ENCLAVE);
+        <AssetConfigs<T>>::insert(ASSET, AssetDepositConfig {
+            enabled: true,
+            min_deposit: balance::<T>(1),
+            max_deposit: Some(balance::<T>(1_000_000)),
+            max_per_account: Some(balance::<T>(1_000_000)),
+        });
+        <T as Config>::Currency::deposit(ASSET, &caller, balance::<T>(1_000_000))?;
+    }: _(RawOrigin::Signed(caller.clone()), ASSET, balance::<T>(1_000))
+    verify {
+        assert_eq!(Module::<T>::account_deposits(&caller, ASSET), balance::<T>(1_000));
+    }
+
+    release {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        fund_custody::<T>(1_000_000)?;
+        set_outflow_limit::<T>();
+        let withdrawal_id = request_withdrawal::<T>(0, 1_000)?;
+    }: _(RawOrigin::Signed(enclave), withdrawal_id, 1)
+    verify {
+        assert!(matches!(
+            Module::<T>::withdrawals(withdrawal_id).map(|request| request.status),
+            Some(WithdrawalStatus::Settled(_))
+        ));
+    }
+
+    release_batch {
+        let n in 1 .. T::MaxReleaseBatch::get();
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        fund_custody::<T>(1_000_000_000)?;
+        set_outflow_limit::<T>();
+        let mut withdrawal_ids = Vec::new();
+        for index in 0 .. n {
+            withdrawal_ids.push(request_withdrawal::<T>(index, 1_000)?);
+        }
+    }: _(RawOrigin::Signed(enclave), withdrawal_ids, 1)
+    verify {
+        assert_eq!(<PendingWithdrawals<T>>::iter().count(), 0);
+    }
+
+    reject_withdrawal {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let withdrawal_id = request_withdrawal::<T>(0, 1_000)?;
+    }: _(RawOrigin::Signed(enclave), withdrawal_id, 1)
+    verify {
+        assert!(matches!(
+            Module::<T>::withdrawals(withdrawal_id).map(|request| request.status),
+            Some(WithdrawalStatus::Rejected(_))
+        ));
+    }
+
+    commit_balance_snapshot {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let user: T::AccountId = account("user", 0, SEED);
+        let root = commit_snapshot::<T>(1, &user, 1_000);
+    }: _(RawOrigin::Signed(enclave), 2, root)
+    verify {
+        assert_eq!(Module::<T>::latest_snapshot_epoch(), Some(2));
+    }
+
+    heartbeat {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        <LiveEnclaves<T>>::kill();
+    }: _(RawOrigin::Signed(enclave.clone()))
+    verify {
+        assert!(Module::<T>::live_enclaves().contains(&enclave));
+    }
+
+    post_trades {
+        let n in 1 .. T::MaxFillsPerBatch::get();
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let market = open_market::<T>();
+        let maker: T::AccountId = account("maker", 0, SEED);
+        let taker: T::AccountId = account("taker", 0, SEED);
+        let fills: Vec<FillOf<T>> = (0 .. n).map(|_| Fill {
+            market,
+            price: balance::<T>(10),
+            quantity: balance::<T>(100),
+            maker: maker.clone(),
+            taker: taker.clone(),
+            fee: balance::<T>(1),
+        }).collect();
+    }: _(RawOrigin::Signed(enclave), fills)
+    verify {
+        assert_eq!(Module::<T>::next_trade_batch_id(), 1);
+    }
+
+    register_market {
+        let market = TradingPair {
+            base: AssetId::POLKADEX,
+            quote: AssetId::DOT,
+        };
+        let call = Call::<T>::register_market(market, balance::<T>(1), balance::<T>(1), balance::<T>(1));
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::market(market).is_some());
+    }
+
+    update_market {
+        let market = open_market::<T>();
+        let call = Call::<T>::update_market(market, balance::<T>(10), balance::<T>(10), balance::<T>(100));
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert_eq!(Module::<T>::market(market).map(|info| info.tick_size), Some(balance::<T>(10)));
+    }
+
+    set_market_status {
+        let market = open_market::<T>();
+        let call = Call::<T>::set_market_status(market, MarketStatus::Halted);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert_eq!(Module::<T>::market(market).map(|info| info.status), Some(MarketStatus::Halted));
+    }
+
+    set_fee_schedule {
+        let market = open_market::<T>();
+        let schedule = FeeSchedule {
+            maker: Permill::from_percent(1),
+            taker: Permill::from_percent(2),
+        };
+        let call = Call::<T>::set_fee_schedule(market, schedule);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert_eq!(Module::<T>::market_fees(market), Some(schedule));
+    }
+
+    report_fees {
+        let n in 1 .. T::MaxLiabilityAssets::get();
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let fees: Vec<(AssetId, T::Balance)> = (0 .. n)
+            .map(|index| (AssetId::TOKEN(sp_core::H160::from_low_u64_be(index as u64)), balance::<T>(1_000)))
+            .collect();
+        // Every fee is covered by custody funds above the liabilities
+        for (asset_id, _) in fees.iter() {
+            <T as Config>::Currency::deposit(*asset_id, &Module::<T>::get_account(), balance::<T>(1_000))?;
+        }
+        let liabilities: Vec<(AssetId, T::Balance)> =
+            fees.iter().map(|(asset_id, _)| (*asset_id, Zero::zero())).collect();
+        <Liabilities<T>>::put(liabilities);
+    }: _(RawOrigin::Signed(enclave), fees)
+    verify {
+        assert_eq!(Module::<T>::accrued_fees(AssetId::TOKEN(sp_core::H160::from_low_u64_be(0))), balance::<T>(1_000));
+    }
+
+    report_liabilities {
+        let n in 1 .. T::MaxLiabilityAssets::get();
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let liabilities: Vec<(AssetId, T::Balance)> = (0 .. n)
+            .map(|index| (AssetId::TOKEN(sp_core::H160::from_low_u64_be(index as u64)), balance::<T>(1_000)))
+            .collect();
+    }: _(RawOrigin::Signed(enclave), liabilities, 1)
+    verify {
+        assert_eq!(Module::<T>::liabilities().len(), n as usize);
+        assert_eq!(Module::<T>::liabilities_nonce(), 1);
+    }
+
+    sweep_fees {
+        let caller: T::AccountId = whitelisted_caller();
+        fund_custody::<T>(1_000_000)?;
+        <Liabilities<T>>::put(vec![(ASSET, balance::<T>(500_000))]);
+        <AccruedFees<T>>::insert(ASSET, balance::<T>(1_000));
+    }: _(RawOrigin::Signed(caller), ASSET)
+    verify {
+        assert!(Module::<T>::accrued_fees(ASSET).is_zero());
+    }
+
+    announce_successor {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        allow_mrenclave::<T>(SUCCESSOR);
+    }: _(RawOrigin::Signed(enclave), SUCCESSOR)
+    verify {
+        assert!(Module::<T>::pending_handover().is_some());
+    }
+
+    acknowledge_handover {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let successor = add_enclave::<T>(1, SUCCESSOR);
+        let user: T::AccountId = account("user", 0, SEED);
+        let root = commit_snapshot::<T>(1, &user, 1_000);
+        Module::<T>::announce_successor(RawOrigin::Signed(enclave.clone()).into(), SUCCESSOR)?;
+    }: _(RawOrigin::Signed(successor), root)
+    verify {
+        assert!(Module::<T>::is_retired(&enclave));
+    }
+
+    cancel_handover {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        allow_mrenclave::<T>(SUCCESSOR);
+        Module::<T>::announce_successor(RawOrigin::Signed(enclave).into(), SUCCESSOR)?;
+        let call = Call::<T>::cancel_handover();
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::pending_handover().is_none());
+    }
+
+    exodus_claim {
+        let p in 0 .. 32;
+        let caller: T::AccountId = whitelisted_caller();
+        fund_custody::<T>(1_000_000)?;
+        // Fold the proof the same way the verification does
+        let mut root = Module::<T>::balance_leaf(&caller, ASSET, balance::<T>(1_000));
+        let mut proof = Vec::new();
+        for index in 0 .. p {
+            let sibling = T::Hashing::hash_of(&index);
+            root = if root <= sibling {
+                T::Hashing::hash_of(&(root, sibling))
+            } else {
+                T::Hashing::hash_of(&(sibling, root))
+            };
+            proof.push(sibling);
+        }
+        <BalanceSnapshots<T>>::insert(1, root);
+        <SnapshotFlows<T>>::insert(&caller, ASSET, SnapshotFlow {
+            epoch: Some(1),
+            deposited: balance::<T>(500),
+            withdrawn: balance::<T>(200),
+        });
+        ExodusMode::put(1);
+    }: _(RawOrigin::Signed(caller.clone()), ASSET, balance::<T>(1_000), proof)
+    verify {
+        assert!(<ExodusClaims<T>>::get((1, caller), ASSET));
+    }
+
+    exit_exodus_mode {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        ExodusMode::put(1);
+        let call = Call::<T>::exit_exodus_mode(enclave);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::exodus_mode().is_none());
+    }
+
+    set_asset_config {
+        let config = AssetDepositConfig {
+            enabled: true,
+            min_deposit: balance::<T>(1),
+            max_deposit: Some(balance::<T>(1_000_000)),
+            max_per_account: Some(balance::<T>(1_000_000)),
+        };
+        let call = Call::<T>::set_asset_config(ASSET, config);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::asset_config(ASSET).is_some());
+    }
+
+    set_release_threshold {
+        let call = Call::<T>::set_release_threshold(2);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert_eq!(Module::<T>::release_threshold(), 2);
+    }
+
+    set_outflow_limit {
+        let limit = OutflowLimit {
+            max_amount: balance::<T>(1_000_000),
+            period: 100u32.into(),
+        };
+        let call = Call::<T>::set_outflow_limit(ASSET, Some(limit));
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::outflow_limit(ASSET).is_some());
+    }
+
+    approve_queued_release {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        fund_custody::<T>(1_000_000)?;
+        let withdrawal_id = request_withdrawal::<T>(0, 1_000)?;
+        <QueuedReleases<T>>::insert(withdrawal_id, QueuedRelease {
+            enclave,
+            mr_enclave: MR_ENCLAVE,
+            unlock_at: Zero::zero(),
+        });
+        let call = Call::<T>::approve_queued_release(withdrawal_id);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::queued_release(withdrawal_id).is_none());
+    }
+
+    cancel_queued_release {
+        let enclave = add_enclave::<T>(0, MR_ENCLAVE);
+        let withdrawal_id = request_withdrawal::<T>(0, 1_000)?;
+        <QueuedReleases<T>>::insert(withdrawal_id, QueuedRelease {
+            enclave,
+            mr_enclave: MR_ENCLAVE,
+            unlock_at: Zero::zero(),
+        });
+        let call = Call::<T>::cancel_queued_release(withdrawal_id);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::queued_release(withdrawal_id).is_none());
+    }
+
+    set_challenge_window {
+        let call = Call::<T>::set_challenge_window(Some(10u32.into()));
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert_eq!(Module::<T>::challenge_window(), Some(10u32.into()));
+    }
+
+    challenge_release {
+        let withdrawal_id = delayed_release::<T>()?;
+        let watcher = watcher::<T>()?;
+    }: _(RawOrigin::Signed(watcher), withdrawal_id)
+    verify {
+        assert_eq!(
+            Module::<T>::withdrawals(withdrawal_id).map(|request| request.status),
+            Some(WithdrawalStatus::Challenged)
+        );
+    }
+
+    cancel_delayed_release {
+        let withdrawal_id = delayed_release::<T>()?;
+        challenge::<T>(withdrawal_id)?;
+        let call = Call::<T>::cancel_delayed_release(withdrawal_id);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::delayed_release(withdrawal_id).is_none());
+    }
+
+    dismiss_challenge {
+        let withdrawal_id = delayed_release::<T>()?;
+        challenge::<T>(withdrawal_id)?;
+        // The window passed, so the release is rescheduled as well
+        <system::Module<T>>::set_block_number(20u32.into());
+        let call = Call::<T>::dismiss_challenge(withdrawal_id);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::delayed_release(withdrawal_id).map_or(false, |delayed| delayed.challenge.is_none()));
+    }
+
+    execute_delayed_release {
+        let caller: T::AccountId = whitelisted_caller();
+        let withdrawal_id = delayed_release::<T>()?;
+        <system::Module<T>>::set_block_number(20u32.into());
+    }: _(RawOrigin::Signed(caller), withdrawal_id)
+    verify {
+        assert!(matches!(
+            Module::<T>::withdrawals(withdrawal_id).map(|request| request.status),
+            Some(WithdrawalStatus::Settled(_))
+        ));
+    }
+
+    set_paused {
+        let call = Call::<T>::set_paused(OcexOperation::Release, Some(ASSET), true);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::asset_paused(OcexOperation::Release, ASSET));
+    }
+
+    allow_mrenclave {
+        let call = Call::<T>::allow_mrenclave(MR_ENCLAVE, Some(100u32.into()));
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::allowed_mrenclave(MR_ENCLAVE).is_some());
+    }
+
+    remove_mrenclave {
+        allow_mrenclave::<T>(MR_ENCLAVE);
+        let call = Call::<T>::remove_mrenclave(MR_ENCLAVE);
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert!(Module::<T>::allowed_mrenclave(MR_ENCLAVE).is_none());
+    }
+
+    expire_mrenclave {
+        allow_mrenclave::<T>(MR_ENCLAVE);
+        let call = Call::<T>::expire_mrenclave(MR_ENCLAVE, 100u32.into());
+    }: { call.dispatch_bypass_filter(governance::<T>())? }
+    verify {
+        assert_eq!(
+            Module::<T>::allowed_mrenclave(MR_ENCLAVE).and_then(|info| info.expires_at),
+            Some(100u32.into())
+        );
+    }
+
+    // Authorities which ignore static keys reject the call, which is what they are weighed by
+    add_static_enclave {
+        let enclave: T::AccountId = account("enclave", 0, SEED);
+        let call = Call::<T>::add_static_enclave(enclave.clone(), MR_ENCLAVE);
+    }: { let _ = call.dispatch_bypass_filter(governance::<T>()); }
+    verify {
+        if T::EnclaveAuthority::accepts_static_keys() {
+            assert_eq!(Module::<T>::static_enclave(&enclave), Some(MR_ENCLAVE));
+        }
+    }
+
+    remove_static_enclave {
+        let enclave: T::AccountId = account("enclave", 0, SEED);
+        <StaticEnclaveKeys<T>>::insert(&enclave, MR_ENCLAVE);
+        let call = Call::<T>::remove_static_enclave(enclave.clone());
+    }: { let _ = call.dispatch_bypass_filter(governance::<T>()); }
+    verify {
+        if T::EnclaveAuthority::accepts_static_keys() {
+            assert!(Module::<T>::static_enclave(&enclave).is_none());
+        }
+    }
+
+    register_trading_key {
+        let caller: T::AccountId = whitelisted_caller();
+        let key: T::TradingKey = sr25519::Public::from_raw([1u8; 32]).into();
+        for index in 1 .. T::MaxTradingKeys::get() {
+            let other: T::TradingKey = sr25519::Public::from_raw([index as u8 + 1; 32]).into();
+            register_key::<T>(&caller, other)?;
+        }
+    }: _(RawOrigin::Signed(caller.clone()), key.clone())
+    verify {
+        assert_eq!(Module::<T>::pending_trading_key(&caller), Some(key));
+    }
+
+    confirm_trading_key {
+        let caller: T::AccountId = whitelisted_caller();
+        let key: T::TradingKey = sr25519::Public::from_raw([1u8; 32]).into();
+        let key_account = key.clone().into_account();
+        for index in 1 .. T::MaxTradingKeys::get() {
+            let other: T::TradingKey = sr25519::Public::from_raw([index as u8 + 1; 32]).into();
+            register_key::<T>(&caller, other)?;
+        }
+        Module::<T>::register_trading_key(RawOrigin::Signed(caller.clone()).into(), key)?;
+    }: _(RawOrigin::Signed(key_account.clone()), caller.clone())
+    verify {
+        assert_eq!(Module::<T>::trading_key_owner(&key_account), Some(caller));
+    }
+
+    revoke_trading_key {
+        let caller: T::AccountId = whitelisted_caller();
+        let key: T::TradingKey = sr25519::Public::from_raw([1u8; 32]).into();
+        let key_account = key.clone().into_account();
+        register_key::<T>(&caller, key.clone())?;
+    }: _(RawOrigin::Signed(caller), key)
+    verify {
+        assert!(Module::<T>::trading_key_owner(&key_account).is_none());
+    }
+
+    register_trader {
+        let caller: T::AccountId = whitelisted_caller();
+        let stake = T::MinTraderStake::get();
+        <T as Config>::Currency::deposit(AssetId::POLKADEX, &caller, stake.saturating_add(balance::<T>(1_000_000)))?;
+    }: _(RawOrigin::Signed(caller.clone()), stake)
+    verify {
+        assert_eq!(Module::<T>::trader_stake(&caller), Some(stake));
+    }
+
+    unregister_trader {
+        let caller: T::AccountId = whitelisted_caller();
+        let stake = T::MinTraderStake::get();
+        <T as Config>::Currency::deposit(AssetId::POLKADEX, &caller, stake.saturating_add(balance::<T>(1_000_000)))?;
+        Module::<T>::register_trader(RawOrigin::Signed(caller.clone()).into(), stake)?;
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        assert!(Module::<T>::trader_stake(&caller).is_none());
+    }
+
+    withdraw_unbonded_stake {
+        let caller: T::AccountId = whitelisted_caller();
+        let stake = T::MinTraderStake::get();
+        <T as Config>::Currency::deposit(AssetId::POLKADEX, &caller, stake.saturating_add(balance::<T>(1_000_000)))?;
+        Module::<T>::register_trader(RawOrigin::Signed(caller.clone()).into(), stake)?;
+        Module::<T>::unregister_trader(RawOrigin::Signed(caller.clone()).into())?;
+        let unlock_at = <system::Module<T>>::block_number().saturating_add(T::TraderUnbondingPeriod::get());
+        <system::Module<T>>::set_block_number(unlock_at);
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        assert!(Module::<T>::unbonding_trader_stake(&caller).is_none());
+    }
+
+    withdraw {
+        let caller: T::AccountId = whitelisted_caller();
+    }: _(RawOrigin::Signed(caller.clone()), ASSET, caller.clone(), balance::<T>(1_000))
+    verify {
+        assert!(Module::<T>::pending_withdrawals(&caller, 0));
+    }
+}
+
+impl_benchmark_test_suite!(Module, crate::mock::new_tester(), crate::mock::Test);
@@ -0,0 +1,125 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transaction payment with feeless order calls for registered traders
+
+use super::*;
+
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::traits::{DispatchInfoOf, PostDispatchInfoOf, SignedExtension};
+use sp_runtime::transaction_validity::{
+    TransactionValidity, TransactionValidityError, ValidTransaction,
+};
+
+/// Charges transaction fees like `ChargeTransactionPayment`, except for the calls accepted by
+/// `FeelessCalls` which registered traders send within their rate limit and stake quota
+///
+/// Over the limits the transaction pays its fee as usual. A trader has one feeless transaction
+/// in the pool at a time, as the quota is only counted when transactions are included and
+/// rejected feeless transactions cost nothing (see `Module::feeless_tag`). The
+/// encoding is the one of the wrapped extension, so wallets keep sending the tip they already
+/// know.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct FeelessTransactionPayment<T: Config + pallet_transaction_payment::Config>(
+    ChargeTransactionPayment<T>,
+);
+
+impl<T: Config + pallet_transaction_payment::Config> From<ChargeTransactionPayment<T>>
+    for FeelessTransactionPayment<T>
+{
+    fn from(payment: ChargeTransactionPayment<T>) -> Self {
+        Self(payment)
+    }
+}
+
+impl<T: Config + pallet_transaction_payment::Config> sp_std::fmt::Debug
+    for FeelessTransactionPayment<T>
+{
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "FeelessTransactionPayment({:?})", self.0)
+    }
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T> SignedExtension for FeelessTransactionPayment<T>
+where
+    T: Config + pallet_transaction_payment::Config + Send + Sync,
+    ChargeTransactionPayment<T>: SignedExtension<
+        AccountId = T::AccountId,
+        Call = <T as system::Config>::Call,
+        AdditionalSigned = (),
+    >,
+{
+    const IDENTIFIER: &'static str = <ChargeTransactionPayment<T> as SignedExtension>::IDENTIFIER;
+    type AccountId = T::AccountId;
+    type Call = <T as system::Config>::Call;
+    type AdditionalSigned = ();
+    /// `None` for feeless transactions
+    type Pre = Option<<ChargeTransactionPayment<T> as SignedExtension>::Pre>;
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> TransactionValidity {
+        if let Some(trader) = Module::<T>::feeless_trader(who, call) {
+            return Ok(ValidTransaction {
+                provides: sp_std::vec![Module::<T>::feeless_tag(&trader)],
+                ..Default::default()
+            });
+        }
+        self.0.validate(who, call, info, len)
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        if let Some(trader) = Module::<T>::feeless_trader(who, call) {
+            Module::<T>::note_feeless_transaction(&trader);
+            return Ok(None);
+        }
+        self.0.pre_dispatch(who, call, info, len).map(Some)
+    }
+
+    fn post_dispatch(
+        pre: Self::Pre,
+        info: &DispatchInfoOf<Self::Call>,
+        post_info: &PostDispatchInfoOf<Self::Call>,
+        len: usize,
+        result: &DispatchResult,
+    ) -> Result<(), TransactionValidityError> {
+        match pre {
+            Some(pre) => ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result),
+            None => Ok(()),
+        }
+    }
+}
@@ -18,22 +18,66 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::StorageMap;
 use frame_support::{
-    decl_error, decl_event, decl_module, dispatch::DispatchResult, ensure, traits::Get, PalletId,
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+    traits::{BalanceStatus, EnsureOrigin, Filter, Get},
+    transactional,
+    weights::Weight,
+    PalletId, Parameter,
 };
 use frame_system as system;
 use frame_system::ensure_signed;
-use orml_traits::{MultiCurrency, MultiCurrencyExtended};
+use orml_traits::{MultiCurrency, MultiCurrencyExtended, MultiReservableCurrency};
 use polkadex_primitives::assets::AssetId;
-use sp_runtime::traits::AccountIdConversion;
+use polkadex_primitives::markets::{MarketInfo, MarketRegistry, MarketStatus, TradingPair};
+use polkadex_primitives::ocex::{
+    EnclaveStatus, OcexOperation, ReserveStatus, WithdrawalRequest, WithdrawalStatus,
+};
+use sp_runtime::traits::{
+    AccountIdConversion, CheckedAdd, CheckedDiv, Hash, IdentifyAccount, Member, One, Saturating,
+    UniqueSaturatedInto, Zero,
+};
+use sp_runtime::{FixedPointNumber, FixedU128, Permill};
 use sp_std::prelude::*;
 // pub(crate) type BalanceOf<T> = <T as orml_tokens::Config>::Balance;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod test;
+
+mod authority;
+mod feeless;
+pub mod weights;
+pub use authority::{EnclaveAuthority, RegistryEnclaves, StaticEnclaves};
+pub use feeless::FeelessTransactionPayment;
+pub use weights::WeightInfo;
+
+/// SGX measurement (MRENCLAVE) of an enclave build
+pub type MrEnclave = [u8; 32];
+
+/// Unique identifier of an on-chain withdrawal request
+pub type WithdrawalId = u64;
+
+/// Epoch of a balance snapshot committed by the enclave
+pub type SnapshotEpoch = u64;
+
+/// Identifier of a batch of fills posted by the enclave
+pub type TradeBatchId = u64;
 
 
 pub trait Config:
-    system::Config + orml_tokens::Config + pallet_substratee_registry::Config
+    system::Config
+    + orml_tokens::Config<CurrencyId = AssetId>
+    + polkadex_fungible_assets::Config
 {
     /// Events
     type Event: From<Event<Self>> + Into<<Self as system::Config>::Event>;
@@ -44,25 +88,538 @@ pub trait Config:
         Self::AccountId,
         CurrencyId = AssetId,
         Balance = Self::Balance,
-    >;
+    > + MultiReservableCurrency<Self::AccountId>;
+    /// Origin allowed to manage the accepted enclave builds
+    type GovernanceOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+    /// Enclaves known to OCEX, with the build they run
+    type EnclaveAuthority: EnclaveAuthority<Self::AccountId>;
+    /// Maximum number of withdrawals settled by one `release_batch`
+    type MaxReleaseBatch: Get<u32>;
+    /// Number of blocks settled and rejected withdrawal requests are kept before they are pruned
+    type WithdrawalRetention: Get<Self::BlockNumber>;
+    /// Number of blocks without enclave activity after which the exodus mode starts
+    type ExodusTimeout: Get<Self::BlockNumber>;
+    /// Maximum number of blocks between two heartbeats of a live enclave
+    type HeartbeatInterval: Get<Self::BlockNumber>;
+    /// Number of blocks a release over the outflow limit waits before governance can approve it
+    type QueuedReleaseDelay: Get<Self::BlockNumber>;
+    /// Number of buckets the outflow period is split into, the outflow window moves by one bucket
+    type OutflowBucketsPerPeriod: Get<u32>;
+    /// Number of blocks a release payload collects enclave approvals before it expires
+    type ApprovalTimeout: Get<Self::BlockNumber>;
+    /// Number of blocks the successor enclave has to take over after a handover announcement
+    type HandoverTimeout: Get<Self::BlockNumber>;
+    /// Maximum number of fills in one `post_trades` batch
+    type MaxFillsPerBatch: Get<u32>;
+    /// Public key a bot signs its orders with on behalf of a main account
+    type TradingKey: Parameter + Member + IdentifyAccount<AccountId = Self::AccountId>;
+    /// Maximum number of trading keys of one main account
+    type MaxTradingKeys: Get<u32>;
+    /// Calls registered traders can send without paying transaction fees
+    type FeelessCalls: Filter<<Self as system::Config>::Call>;
+    /// Minimum PDEX stake of a registered trader
+    type MinTraderStake: Get<Self::Balance>;
+    /// PDEX stake backing one feeless transaction per window
+    type StakePerFeelessTransaction: Get<Self::Balance>;
+    /// Number of blocks after which the feeless quota of a trader is renewed
+    type FeelessWindow: Get<Self::BlockNumber>;
+    /// Maximum number of feeless transactions of one trader in a block
+    type MaxFeelessPerBlock: Get<u32>;
+    /// Number of blocks the stake of an unregistered trader stays reserved
+    type TraderUnbondingPeriod: Get<Self::BlockNumber>;
+    /// PDEX reserved from a watcher challenging a delayed release
+    type ChallengeBond: Get<Self::Balance>;
+    /// Maximum number of delayed releases paid out in one block
+    type MaxDelayedReleasesPerBlock: Get<u32>;
+    /// Maximum number of assets in a liabilities report of the enclave
+    type MaxLiabilityAssets: Get<u32>;
+    /// Weight information for the extrinsics of this pallet
+    type WeightInfo: WeightInfo;
+}
+
+/// Governance approval of an enclave build
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct MrEnclaveInfo<BlockNumber> {
+    /// Block in which the measurement was allowed
+    pub allowed_at: BlockNumber,
+    /// Block from which the measurement is no longer accepted
+    pub expires_at: Option<BlockNumber>,
+}
+
+impl<BlockNumber: PartialOrd> MrEnclaveInfo<BlockNumber> {
+    pub fn is_expired(&self, now: &BlockNumber) -> bool {
+        match &self.expires_at {
+            Some(expires_at) => now >= expires_at,
+            None => false,
+        }
+    }
+}
+
+/// Deposit rules of an asset tradable on the exchange
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct AssetDepositConfig<Balance> {
+    pub enabled: bool,
+    /// Smallest accepted deposit
+    pub min_deposit: Balance,
+    /// Largest accepted single deposit
+    pub max_deposit: Option<Balance>,
+    /// Largest amount an account can hold in custody, net of settled withdrawals
+    pub max_per_account: Option<Balance>,
+}
+
+/// Maximum amount of an asset released by enclaves per period
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct OutflowLimit<Balance, BlockNumber> {
+    pub max_amount: Balance,
+    /// Length of the window in blocks
+    pub period: BlockNumber,
+}
+
+/// Amount released in one bucket of the rolling outflow window
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct OutflowBucket<Balance, BlockNumber> {
+    /// Block in which the bucket started
+    pub start: BlockNumber,
+    pub released: Balance,
+}
+
+/// Release waiting for its challenge window to pass
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct DelayedRelease<AccountId, Balance, BlockNumber> {
+    /// Enclave which released the withdrawal
+    pub enclave: AccountId,
+    pub mr_enclave: MrEnclave,
+    /// Block in which the release is paid out unless challenged
+    pub execute_at: BlockNumber,
+    pub challenge: Option<ReleaseChallenge<AccountId, Balance>>,
+}
+
+/// Challenge of a delayed release by a watcher
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct ReleaseChallenge<AccountId, Balance> {
+    pub watcher: AccountId,
+    /// PDEX reserved from the watcher, slashed if governance dismisses the challenge
+    pub bond: Balance,
+}
+
+/// Release held back because it exceeded the outflow limit
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct QueuedRelease<AccountId, BlockNumber> {
+    /// Enclave which released the withdrawal
+    pub enclave: AccountId,
+    pub mr_enclave: MrEnclave,
+    /// Block from which governance can approve the release
+    pub unlock_at: BlockNumber,
+}
+
+/// Enclave approvals collected for a release payload
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct ReleaseApproval<AccountId, BlockNumber> {
+    /// Distinct enclaves which approved the payload
+    pub approvals: Vec<AccountId>,
+    pub expires_at: BlockNumber,
+}
+
+/// Deposits and payouts of an account made after the snapshot they were recorded against
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SnapshotFlow<Balance> {
+    /// Latest snapshot epoch when the flows were recorded
+    pub epoch: Option<SnapshotEpoch>,
+    pub deposited: Balance,
+    pub withdrawn: Balance,
+}
+
+/// Software upgrade announced by a running enclave
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct EnclaveHandover<AccountId, BlockNumber> {
+    /// Enclave handing over its release authority
+    pub old_enclave: AccountId,
+    /// Measurement of the build handing over
+    pub old_build: MrEnclave,
+    /// Measurement of the successor build
+    pub successor: MrEnclave,
+    /// Block after which the handover is rolled back
+    pub deadline: BlockNumber,
+}
+
+/// Trade executed by the enclave matching engine
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Fill<AccountId, Balance> {
+    pub market: TradingPair,
+    /// Price in quote asset
+    pub price: Balance,
+    /// Quantity in base asset
+    pub quantity: Balance,
+    pub maker: AccountId,
+    pub taker: AccountId,
+    pub fee: Balance,
+}
+
+pub type FillOf<T> = Fill<<T as system::Config>::AccountId, <T as orml_tokens::Config>::Balance>;
+
+/// Trading fees of a market
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct FeeSchedule {
+    pub maker: Permill,
+    pub taker: Permill,
+}
+
+/// Feeless transactions sent by a trader
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, Default)]
+pub struct FeelessUsage<BlockNumber> {
+    /// Block in which the quota window started
+    pub window_start: BlockNumber,
+    pub in_window: u32,
+    pub block: BlockNumber,
+    pub in_block: u32,
+}
+
+pub type DelayedReleaseOf<T> = DelayedRelease<
+    <T as system::Config>::AccountId,
+    <T as orml_tokens::Config>::Balance,
+    <T as system::Config>::BlockNumber,
+>;
+
+pub type WithdrawalRequestOf<T> = WithdrawalRequest<
+    <T as system::Config>::AccountId,
+    <T as orml_tokens::Config>::Balance,
+    <T as system::Config>::BlockNumber,
+>;
+
+decl_storage! {
+    trait Store for Module<T: Config> as PolkadexOcex {
+        /// MRENCLAVE measurements accepted by governance
+        AllowedMrEnclaves get(fn allowed_mrenclave): map hasher(blake2_128_concat) MrEnclave => Option<MrEnclaveInfo<T::BlockNumber>>;
+        /// Enclave keys set by governance with the build they run, used by `StaticEnclaves`
+        StaticEnclaveKeys get(fn static_enclave): map hasher(blake2_128_concat) T::AccountId => Option<MrEnclave>;
+        /// Identifier of the next withdrawal request
+        NextWithdrawalId get(fn next_withdrawal_id): WithdrawalId;
+        /// All withdrawal requests with their settlement status
+        Withdrawals get(fn withdrawals): map hasher(twox_64_concat) WithdrawalId => Option<WithdrawalRequestOf<T>>;
+        /// Withdrawal requests of an account which are still waiting for the enclave
+        PendingWithdrawals get(fn pending_withdrawals): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) WithdrawalId => bool;
+        /// Amount of the open withdrawal requests of an account per asset
+        PendingWithdrawalAmounts get(fn pending_withdrawal_amount): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) AssetId => T::Balance;
+        /// Settled and rejected withdrawal requests pruned in a block
+        WithdrawalPruneSchedule get(fn withdrawals_pruned_at): map hasher(twox_64_concat) T::BlockNumber => Vec<WithdrawalId>;
+        /// Last nonce used by an enclave account
+        EnclaveNonces get(fn enclave_nonce): map hasher(blake2_128_concat) T::AccountId => u64;
+        /// Merkle root of all user balances held in custody, per epoch
+        BalanceSnapshots get(fn balance_snapshot): map hasher(twox_64_concat) SnapshotEpoch => Option<T::Hash>;
+        /// Epoch of the most recent balance snapshot
+        LatestSnapshotEpoch get(fn latest_snapshot_epoch): Option<SnapshotEpoch>;
+        /// Last block in which an enclave acted on the custody account
+        LastEnclaveActivity get(fn last_enclave_activity): T::BlockNumber;
+        /// Snapshot epoch users claim against while the exodus mode is active
+        ExodusMode get(fn exodus_mode): Option<SnapshotEpoch>;
+        /// Balances already claimed from a snapshot during the exodus mode
+        ExodusClaims get(fn exodus_claimed): double_map hasher(blake2_128_concat) (SnapshotEpoch, T::AccountId), hasher(blake2_128_concat) AssetId => bool;
+        /// Deposits and payouts not yet covered by the latest balance snapshot
+        SnapshotFlows get(fn snapshot_flow): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) AssetId => SnapshotFlow<T::Balance>;
+        /// Block of the last heartbeat of an enclave
+        EnclaveHeartbeats get(fn enclave_heartbeat): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+        /// Enclaves whose last heartbeat is within `HeartbeatInterval`
+        LiveEnclaves get(fn live_enclaves): Vec<T::AccountId>;
+        /// Operations paused for all assets
+        GlobalPause get(fn globally_paused): map hasher(twox_64_concat) OcexOperation => bool;
+        /// Operations paused for a single asset
+        AssetPause get(fn asset_paused): double_map hasher(twox_64_concat) OcexOperation, hasher(blake2_128_concat) AssetId => bool;
+        /// Assets accepted for deposit and their limits
+        AssetConfigs get(fn asset_config): map hasher(blake2_128_concat) AssetId => Option<AssetDepositConfig<T::Balance>>;
+        /// Amount deposited by an account, net of settled withdrawals
+        AccountDeposits get(fn account_deposits): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) AssetId => T::Balance;
+        /// Outflow limits set by governance
+        OutflowLimits get(fn outflow_limit): map hasher(blake2_128_concat) AssetId => Option<OutflowLimit<T::Balance, T::BlockNumber>>;
+        /// Amount released per asset in the current window
+        OutflowBuckets get(fn outflow_buckets): map hasher(blake2_128_concat) AssetId => Vec<OutflowBucket<T::Balance, T::BlockNumber>>;
+        /// Releases waiting for governance approval
+        QueuedReleases get(fn queued_release): map hasher(twox_64_concat) WithdrawalId => Option<QueuedRelease<T::AccountId, T::BlockNumber>>;
+        /// Number of blocks releases can be challenged before they are paid out, if enabled
+        ChallengeWindow get(fn challenge_window): Option<T::BlockNumber>;
+        /// Releases waiting for their challenge window to pass
+        DelayedReleases get(fn delayed_release): map hasher(twox_64_concat) WithdrawalId => Option<DelayedReleaseOf<T>>;
+        /// Delayed releases paid out in a block
+        DelayedReleaseSchedule get(fn delayed_releases_at): map hasher(twox_64_concat) T::BlockNumber => Vec<WithdrawalId>;
+        /// Total user balances per asset last reported by the enclave
+        Liabilities get(fn liabilities): Vec<(AssetId, T::Balance)>;
+        /// Nonce of the last liabilities report applied
+        LiabilitiesNonce get(fn liabilities_nonce): u64;
+        /// Assets whose custody reserves are below the reported liabilities
+        UnderReservedAssets get(fn under_reserved_assets): Vec<AssetId>;
+        /// Number of distinct enclaves which must approve a release, at least one
+        ReleaseThreshold get(fn release_threshold): u32;
+        /// Approvals of release payloads which have not reached the threshold yet
+        ReleaseApprovals get(fn release_approval): map hasher(identity) T::Hash => Option<ReleaseApproval<T::AccountId, T::BlockNumber>>;
+        /// Release payloads whose approvals expire in a block
+        ApprovalExpiries get(fn approval_expiries): map hasher(twox_64_concat) T::BlockNumber => Vec<T::Hash>;
+        /// Enclave software upgrade in progress
+        PendingHandover get(fn pending_handover): Option<EnclaveHandover<T::AccountId, T::BlockNumber>>;
+        /// Enclaves which handed over their release authority to a successor
+        RetiredEnclaves get(fn is_retired): map hasher(blake2_128_concat) T::AccountId => bool;
+        /// Identifier of the next batch of fills
+        NextTradeBatchId get(fn next_trade_batch_id): TradeBatchId;
+        /// Hash of the fills of each posted batch
+        TradeBatches get(fn trade_batch): map hasher(twox_64_concat) TradeBatchId => Option<T::Hash>;
+        /// Markets listed on the exchange
+        Markets get(fn market): map hasher(blake2_128_concat) TradingPair => Option<MarketInfo<T::Balance>>;
+        /// Maker and taker fees charged by the enclave per market
+        MarketFees get(fn market_fees): map hasher(blake2_128_concat) TradingPair => Option<FeeSchedule>;
+        /// Fees reported by the enclave and not yet moved to the treasury
+        AccruedFees get(fn accrued_fees): map hasher(blake2_128_concat) AssetId => T::Balance;
+        /// Trading keys registered by a main account
+        TradingKeys get(fn trading_keys): map hasher(blake2_128_concat) T::AccountId => Vec<T::TradingKey>;
+        /// Main account of the account derived from a trading key
+        TradingKeyOwner get(fn trading_key_owner): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+        /// Trading key proposed by a main account and not yet confirmed by the key
+        PendingTradingKeys get(fn pending_trading_key): map hasher(blake2_128_concat) T::AccountId => Option<T::TradingKey>;
+        /// PDEX reserved by traders registered for feeless transactions
+        TraderStakes get(fn trader_stake): map hasher(blake2_128_concat) T::AccountId => Option<T::Balance>;
+        /// Stake of unregistered traders and the block it can be withdrawn from
+        UnbondingTraderStakes get(fn unbonding_trader_stake): map hasher(blake2_128_concat) T::AccountId => Option<(T::Balance, T::BlockNumber)>;
+        /// Feeless transactions of each trader in the current window
+        FeelessUsages get(fn feeless_usage): map hasher(blake2_128_concat) T::AccountId => FeelessUsage<T::BlockNumber>;
+    }
 }
 
 decl_event!(
     pub enum Event<T>
     where
         <T as system::Config>::AccountId,
-        <T as orml_tokens::Config>::Balance
+        <T as orml_tokens::Config>::Balance,
+        <T as system::Config>::BlockNumber,
+        <T as system::Config>::Hash
     {
         TokenDeposited(AssetId, AccountId, Balance),
-        TokenWithdrawn(AssetId, AccountId, Balance),
+        /// Withdrawal requested (id, asset, account, amount, destination)
+        WithdrawalRequested(WithdrawalId, AssetId, AccountId, Balance, AccountId),
+        /// Withdrawal paid out (id, asset, destination, amount, enclave, enclave MRENCLAVE)
+        WithdrawalSettled(WithdrawalId, AssetId, AccountId, Balance, AccountId, MrEnclave),
+        /// Withdrawal refused by the enclave (id, account, enclave)
+        WithdrawalRejected(WithdrawalId, AccountId, AccountId),
+        /// Batch of withdrawals paid out (enclave, nonce, number of withdrawals)
+        BatchReleased(AccountId, u64, u32),
+        /// Balance snapshot committed (epoch, merkle root, enclave)
+        BalanceSnapshotCommitted(SnapshotEpoch, Hash, AccountId),
+        /// Enclave went silent, users can claim against the snapshot of the given epoch
+        ExodusModeActivated(SnapshotEpoch),
+        /// Governance ended the exodus mode after the given enclave came back
+        ExodusModeDeactivated(AccountId),
+        /// Balance claimed during the exodus mode (epoch, account, asset, amount)
+        ExodusClaimed(SnapshotEpoch, AccountId, AssetId, Balance),
+        /// Enclave started sending heartbeats
+        EnclaveLive(AccountId),
+        /// Enclave missed its heartbeat (enclave, block of the last heartbeat)
+        EnclaveStale(AccountId, BlockNumber),
+        /// Operation halted by governance, for one asset or globally
+        OperationPaused(OcexOperation, Option<AssetId>),
+        /// Operation resumed by governance, for one asset or globally
+        OperationResumed(OcexOperation, Option<AssetId>),
+        /// Deposit rules of an asset were changed
+        AssetConfigUpdated(AssetId),
+        /// Outflow limit of an asset was changed
+        OutflowLimitUpdated(AssetId),
+        /// Release exceeded the outflow limit and waits for governance (id, unlock block)
+        ReleaseQueued(WithdrawalId, BlockNumber),
+        /// Governance cancelled a queued release
+        QueuedReleaseCancelled(WithdrawalId),
+        /// Challenge window changed, `None` pays releases out immediately
+        ChallengeWindowUpdated(Option<BlockNumber>),
+        /// Release is paid out in the given block unless challenged
+        ReleaseDelayed(WithdrawalId, BlockNumber),
+        /// Watcher challenged a delayed release (id, watcher, bond)
+        ReleaseChallenged(WithdrawalId, AccountId, Balance),
+        /// Governance found a challenge frivolous and slashed its bond (id, watcher, bond)
+        ChallengeDismissed(WithdrawalId, AccountId, Balance),
+        /// Governance cancelled a delayed release
+        DelayedReleaseCancelled(WithdrawalId),
+        /// Delayed release could not be paid out and waits for `execute_delayed_release`
+        DelayedReleaseFailed(WithdrawalId, DispatchError),
+        /// Enough enclaves reported the user balances of the given number of assets (last
+        /// reporting enclave, assets)
+        LiabilitiesReported(AccountId, u32),
+        /// Custody reserves fell below the liabilities and releases of the asset were paused
+        /// (asset, reserves, liabilities)
+        ReservesBelowLiabilities(AssetId, Balance, Balance),
+        /// Custody reserves cover the liabilities of the asset again
+        ReservesRestored(AssetId),
+        /// Release threshold changed
+        ReleaseThresholdUpdated(u32),
+        /// Enclave approved a release or fee payload (payload hash, enclave, approvals, threshold)
+        ReleaseApproved(Hash, AccountId, u32, u32),
+        /// Release or fee payload did not reach the threshold in time
+        ReleaseApprovalExpired(Hash),
+        /// Enclave announced its successor build (enclave, successor MRENCLAVE, deadline)
+        HandoverAnnounced(AccountId, MrEnclave, BlockNumber),
+        /// Release authority moved to the successor (old enclave, new enclave, acknowledged root)
+        HandoverCompleted(AccountId, AccountId, Hash),
+        /// Successor did not take over in time or governance cancelled the handover
+        HandoverRolledBack(AccountId, MrEnclave),
+        /// Trade settled off-chain (batch, market, price, quantity, maker, taker, fee)
+        TradeExecuted(TradeBatchId, TradingPair, Balance, Balance, AccountId, AccountId, Balance),
+        /// Batch of fills posted by the enclave (batch, hash of the fills, number of fills)
+        TradeBatchPosted(TradeBatchId, Hash, u32),
+        /// Market listed with status `PreOpen`
+        MarketRegistered(TradingPair),
+        /// Tick size, lot size or minimum notional of a market changed
+        MarketUpdated(TradingPair),
+        MarketStatusChanged(TradingPair, MarketStatus),
+        /// Fee schedule of a market changed (market, maker fee, taker fee)
+        FeeScheduleUpdated(TradingPair, Permill, Permill),
+        /// Enclave reported collected fees (enclave, asset, amount)
+        FeesReported(AccountId, AssetId, Balance),
+        /// Accrued fees moved to the treasury (asset, amount, treasury)
+        FeesSwept(AssetId, Balance, AccountId),
+        /// MRENCLAVE accepted by governance
+        MrEnclaveAllowed(MrEnclave),
+        /// MRENCLAVE removed by governance
+        MrEnclaveRemoved(MrEnclave),
+        /// MRENCLAVE will no longer be accepted from the given block
+        MrEnclaveExpirySet(MrEnclave, BlockNumber),
+        /// Enclave key added by governance (enclave, MRENCLAVE)
+        StaticEnclaveAdded(AccountId, MrEnclave),
+        /// Enclave key removed by governance
+        StaticEnclaveRemoved(AccountId),
+        /// Trading key proposed and waiting for the key to confirm (main account, trading key account)
+        TradingKeyProposed(AccountId, AccountId),
+        /// Trading key registered (main account, trading key account)
+        TradingKeyRegistered(AccountId, AccountId),
+        /// Trading key revoked (main account, trading key account)
+        TradingKeyRevoked(AccountId, AccountId),
+        /// Trader registered for feeless transactions (account, stake)
+        TraderRegistered(AccountId, Balance),
+        /// Trader unregistered and its stake unbonding (account, stake, unlock block)
+        TraderUnregistered(AccountId, Balance, BlockNumber),
+        /// Unbonded stake of a former trader released (account, stake)
+        TraderStakeWithdrawn(AccountId, Balance),
     }
 );
 
-// TODO: Implement a vec of MRENCLAVES set by governance
-
 decl_error! {
     pub enum Error for Module<T: Config> {
         NotARegisteredEnclave,
+        /// Enclave measurement is not in the governance allowlist
+        MrEnclaveNotAllowed,
+        /// Enclave measurement was allowed but has expired
+        MrEnclaveExpired,
+        /// Enclave measurement is already in the allowlist
+        MrEnclaveAlreadyAllowed,
+        /// Enclave key is already set by governance
+        StaticEnclaveExists,
+        StaticEnclaveNotFound,
+        /// Enclave authority does not use the enclave keys set by governance
+        StaticEnclavesNotSupported,
+        /// No withdrawal request with the given id
+        WithdrawalNotFound,
+        /// Withdrawal request was already settled or rejected
+        WithdrawalNotPending,
+        /// Withdrawal amount must not be zero
+        WithdrawalAmountZero,
+        /// Enclave nonce was already used
+        EnclaveNonceReplayed,
+        /// Enclave nonce skips over unused nonces
+        EnclaveNonceOutOfOrder,
+        /// Release batch has no entries
+        EmptyReleaseBatch,
+        /// Release batch has more entries than allowed
+        ReleaseBatchTooLarge,
+        /// Snapshot epoch must be greater than the latest committed epoch
+        SnapshotEpochNotIncreasing,
+        /// Funds can only be claimed through the exodus mode
+        ExodusModeActive,
+        ExodusModeNotActive,
+        /// Balance was already claimed from this snapshot
+        ExodusAlreadyClaimed,
+        /// Payouts since the snapshot used up the proven balance
+        NothingToClaim,
+        /// Balance proof does not match the snapshot
+        InvalidBalanceProof,
+        /// No enclave has sent a heartbeat recently
+        NoLiveEnclave,
+        /// Operation is paused by governance
+        OperationPaused,
+        /// Asset is not enabled for deposits
+        AssetNotEnabled,
+        /// Asset is not verified in the fungible assets registry
+        AssetNotVerified,
+        DepositBelowMinimum,
+        DepositAboveMaximum,
+        /// Deposit would exceed the per-account cap of the asset
+        AccountDepositCapExceeded,
+        /// Withdrawal is not waiting for governance approval
+        ReleaseNotQueued,
+        /// Queued release can not be approved yet
+        ReleaseDelayNotElapsed,
+        /// Challenge window must be at least one block
+        InvalidChallengeWindow,
+        /// Withdrawal has no delayed release
+        ReleaseNotDelayed,
+        ReleaseAlreadyChallenged,
+        ReleaseNotChallenged,
+        /// Delayed release can no longer be challenged
+        ChallengeWindowClosed,
+        /// Delayed release can not be paid out before its challenge window passed
+        ChallengeWindowOpen,
+        /// Liabilities report lists more assets than `MaxLiabilityAssets`
+        TooManyLiabilityAssets,
+        /// Liabilities report lists an asset twice
+        DuplicateLiabilityAsset,
+        /// Liabilities report does not directly follow the last applied one
+        LiabilitiesNonceMismatch,
+        /// Release threshold must be at least one
+        InvalidReleaseThreshold,
+        /// Enclave already approved this release payload
+        ReleaseAlreadyApproved,
+        /// Enclave handed over its release authority
+        EnclaveRetired,
+        /// Another handover is in progress
+        HandoverInProgress,
+        NoPendingHandover,
+        /// Successor enclave has not acknowledged the handover yet
+        HandoverNotAcknowledged,
+        /// Enclave does not run the announced successor build
+        NotTheSuccessor,
+        /// Successor must run a different build than the announcing enclave
+        SuccessorIsCurrentBuild,
+        /// No balance snapshot has been committed yet
+        NoBalanceSnapshot,
+        /// Acknowledged state root is not the last committed snapshot
+        StateRootMismatch,
+        /// Trade batch has no fills
+        EmptyTradeBatch,
+        /// Trade batch has more fills than allowed
+        TradeBatchTooLarge,
+        /// No accrued fees for this asset
+        NoFeesToSweep,
+        /// Accrued fees would exceed the custody balance above the reported liabilities
+        FeesExceedSurplus,
+        MarketAlreadyExists,
+        MarketNotFound,
+        /// Market must trade two different assets with non-zero tick and lot sizes
+        InvalidMarket,
+        /// Delisted markets can not be changed
+        MarketDelisted,
+        /// Fill refers to a market which is not open
+        MarketNotOpen,
+        /// Key is already registered as a trading key
+        TradingKeyInUse,
+        /// Trading keys can not be main accounts or register keys themselves
+        NotAMainAccount,
+        TooManyTradingKeys,
+        TradingKeyNotFound,
+        /// Main account did not propose the confirming key
+        TradingKeyNotProposed,
+        /// Trading keys can only sign orders, not withdrawals
+        TradingKeyCannotWithdraw,
+        /// Withdrawals can not be paid to trading keys
+        TradingKeyDestination,
+        TraderAlreadyRegistered,
+        TraderNotRegistered,
+        /// Stake is below the minimum trader stake
+        TraderStakeTooLow,
+        NoUnbondingStake,
+        /// Unbonding stake can not be withdrawn before its unlock block
+        StakeStillUnbonding,
+        Overflow,
     }
 }
 
@@ -74,32 +631,724 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::expire_heartbeats(now)
+                .saturating_add(Self::prune_withdrawals(now))
+                .saturating_add(Self::check_exodus_timeout(now))
+                .saturating_add(Self::expire_release_approvals(now))
+                .saturating_add(Self::check_handover_deadline(now))
+                .saturating_add(Self::execute_delayed_releases(now))
+                .saturating_add(Self::check_reserves())
+        }
+
         /// Deposit
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::deposit()]
         pub fn deposit(origin, asset_id:  AssetId, amount: T::Balance) -> DispatchResult{
             let from: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            ensure!(!Self::live_enclaves().is_empty(), Error::<T>::NoLiveEnclave);
+            Self::ensure_not_paused(OcexOperation::Deposit, asset_id)?;
+            let total = Self::ensure_deposit_allowed(&from, asset_id, amount)?;
             <T as Config>::Currency::transfer(asset_id, &from, &Self::get_account(), amount)?;
+            <AccountDeposits<T>>::insert(&from, asset_id, total);
+            Self::note_snapshot_flow(&from, asset_id, amount, Zero::zero());
             Self::deposit_event(RawEvent::TokenDeposited(asset_id, from, amount));
             Ok(())
         }
 
         /// Release
-        #[weight = 10000]
-        pub fn release(origin, asset_id:  AssetId, amount: T::Balance, to: T::AccountId) -> DispatchResult{
+        /// Approves a pending withdrawal request and pays it out from the custody account
+        /// once `ReleaseThreshold` enclaves approved it
+        #[weight = <T as Config>::WeightInfo::release()]
+        #[transactional]
+        pub fn release(origin, withdrawal_id: WithdrawalId, nonce: u64) -> DispatchResult{
+            let sender: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            let mr_enclave = Self::ensure_allowed_enclave(&sender)?;
+            Self::ensure_next_nonce(&sender, nonce)?;
+            if Self::approve_release(&sender, &[withdrawal_id])? {
+                Self::settle_withdrawal(withdrawal_id, &sender, mr_enclave)?;
+            }
+            <EnclaveNonces<T>>::insert(&sender, nonce);
+            Self::note_enclave_activity();
+            Ok(())
+        }
+
+        /// Release Batch
+        /// Approves several pending withdrawal requests and pays them out at once, either all
+        /// of them or none, once `ReleaseThreshold` enclaves approved the same batch
+        #[weight = <T as Config>::WeightInfo::release_batch(withdrawal_ids.len() as u32)]
+        #[transactional]
+        pub fn release_batch(origin, withdrawal_ids: Vec<WithdrawalId>, nonce: u64) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            ensure!(!withdrawal_ids.is_empty(), Error::<T>::EmptyReleaseBatch);
+            ensure!(withdrawal_ids.len() <= T::MaxReleaseBatch::get() as usize, Error::<T>::ReleaseBatchTooLarge);
+            Self::ensure_not_exodus()?;
+            let mr_enclave = Self::ensure_allowed_enclave(&sender)?;
+            Self::ensure_next_nonce(&sender, nonce)?;
+            if Self::approve_release(&sender, &withdrawal_ids)? {
+                for withdrawal_id in withdrawal_ids.iter() {
+                    Self::settle_withdrawal(*withdrawal_id, &sender, mr_enclave)?;
+                }
+                Self::deposit_event(RawEvent::BatchReleased(sender.clone(), nonce, withdrawal_ids.len() as u32));
+            }
+            <EnclaveNonces<T>>::insert(&sender, nonce);
+            Self::note_enclave_activity();
+            Ok(())
+        }
+
+        /// Reject a pending withdrawal request
+        #[weight = <T as Config>::WeightInfo::reject_withdrawal()]
+        #[transactional]
+        pub fn reject_withdrawal(origin, withdrawal_id: WithdrawalId, nonce: u64) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            Self::ensure_allowed_enclave(&sender)?;
+            Self::ensure_next_nonce(&sender, nonce)?;
+            let request = Self::pending_withdrawal(withdrawal_id)?;
+            Self::close_withdrawal(withdrawal_id, &request, WithdrawalStatus::Rejected(<system::Module<T>>::block_number()));
+            <EnclaveNonces<T>>::insert(&sender, nonce);
+            Self::note_enclave_activity();
+            Self::deposit_event(RawEvent::WithdrawalRejected(withdrawal_id, request.account, sender));
+            Ok(())
+        }
+
+        /// Commit the merkle root of user balances for an epoch
+        #[weight = <T as Config>::WeightInfo::commit_balance_snapshot()]
+        pub fn commit_balance_snapshot(origin, epoch: SnapshotEpoch, root: T::Hash) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            Self::ensure_allowed_enclave(&sender)?;
+            if let Some(latest) = Self::latest_snapshot_epoch() {
+                ensure!(epoch > latest, Error::<T>::SnapshotEpochNotIncreasing);
+            }
+            <BalanceSnapshots<T>>::insert(epoch, root);
+            LatestSnapshotEpoch::put(epoch);
+            Self::note_enclave_activity();
+            Self::deposit_event(RawEvent::BalanceSnapshotCommitted(epoch, root, sender));
+            Ok(())
+        }
+
+        /// Heartbeat
+        /// Signals that the enclave is alive, expected every `HeartbeatInterval` blocks
+        #[weight = <T as Config>::WeightInfo::heartbeat()]
+        pub fn heartbeat(origin) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_allowed_enclave(&sender)?;
+            <EnclaveHeartbeats<T>>::insert(&sender, <system::Module<T>>::block_number());
+            Self::note_enclave_activity();
+            <LiveEnclaves<T>>::mutate(|live| {
+                if !live.contains(&sender) {
+                    live.push(sender.clone());
+                    Self::deposit_event(RawEvent::EnclaveLive(sender));
+                }
+            });
+            Ok(())
+        }
+
+        /// Post Trades
+        /// Records a batch of fills executed by the enclave
+        #[weight = <T as Config>::WeightInfo::post_trades(fills.len() as u32)]
+        pub fn post_trades(origin, fills: Vec<FillOf<T>>) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            Self::ensure_allowed_enclave(&sender)?;
+            ensure!(!fills.is_empty(), Error::<T>::EmptyTradeBatch);
+            ensure!(fills.len() <= T::MaxFillsPerBatch::get() as usize, Error::<T>::TradeBatchTooLarge);
+            for fill in fills.iter() {
+                let market = Self::market(fill.market).ok_or(Error::<T>::MarketNotFound)?;
+                ensure!(market.status == MarketStatus::Open, Error::<T>::MarketNotOpen);
+            }
+            let batch_id = Self::next_trade_batch_id();
+            let next_id = batch_id.checked_add(1).ok_or(Error::<T>::Overflow)?;
+            let batch_hash = T::Hashing::hash_of(&fills);
+            <TradeBatches<T>>::insert(batch_id, batch_hash);
+            NextTradeBatchId::put(next_id);
+            Self::note_enclave_activity();
+            let count = fills.len() as u32;
+            for fill in fills {
+                Self::deposit_event(RawEvent::TradeExecuted(
+                    batch_id,
+                    fill.market,
+                    fill.price,
+                    fill.quantity,
+                    fill.maker,
+                    fill.taker,
+                    fill.fee,
+                ));
+            }
+            Self::deposit_event(RawEvent::TradeBatchPosted(batch_id, batch_hash, count));
+            Ok(())
+        }
+
+        /// List a new market, trading starts once its status is set to `Open`
+        #[weight = <T as Config>::WeightInfo::register_market()]
+        pub fn register_market(origin, market: TradingPair, tick_size: T::Balance, lot_size: T::Balance, min_notional: T::Balance) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(!<Markets<T>>::contains_key(market), Error::<T>::MarketAlreadyExists);
+            Self::ensure_valid_market(&market, tick_size, lot_size)?;
+            let info = MarketInfo {
+                tick_size,
+                lot_size,
+                min_notional,
+                status: MarketStatus::PreOpen,
+            };
+            <Markets<T>>::insert(market, info);
+            Self::deposit_event(RawEvent::MarketRegistered(market));
+            Ok(())
+        }
+
+        /// Change the trading rules of a market
+        #[weight = <T as Config>::WeightInfo::update_market()]
+        pub fn update_market(origin, market: TradingPair, tick_size: T::Balance, lot_size: T::Balance, min_notional: T::Balance) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            Self::ensure_valid_market(&market, tick_size, lot_size)?;
+            <Markets<T>>::try_mutate(market, |info| {
+                let info = info.as_mut().ok_or(Error::<T>::MarketNotFound)?;
+                ensure!(info.status != MarketStatus::Delisted, Error::<T>::MarketDelisted);
+                info.tick_size = tick_size;
+                info.lot_size = lot_size;
+                info.min_notional = min_notional;
+                Self::deposit_event(RawEvent::MarketUpdated(market));
+                Ok(())
+            })
+        }
+
+        /// Open, halt or delist a market
+        #[weight = <T as Config>::WeightInfo::set_market_status()]
+        pub fn set_market_status(origin, market: TradingPair, status: MarketStatus) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            <Markets<T>>::try_mutate(market, |info| {
+                let info = info.as_mut().ok_or(Error::<T>::MarketNotFound)?;
+                ensure!(info.status != MarketStatus::Delisted, Error::<T>::MarketDelisted);
+                info.status = status;
+                Self::deposit_event(RawEvent::MarketStatusChanged(market, status));
+                Ok(())
+            })
+        }
+
+        /// Set the maker and taker fees of a market
+        #[weight = <T as Config>::WeightInfo::set_fee_schedule()]
+        pub fn set_fee_schedule(origin, market: TradingPair, schedule: FeeSchedule) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(<Markets<T>>::contains_key(market), Error::<T>::MarketNotFound);
+            MarketFees::insert(market, schedule);
+            Self::deposit_event(RawEvent::FeeScheduleUpdated(market, schedule.maker, schedule.taker));
+            Ok(())
+        }
+
+        /// Report Fees
+        /// Adds the fees collected by the enclave per asset to the amount awaiting a sweep once
+        /// `ReleaseThreshold` enclaves reported the same fees
+        ///
+        /// The accrued fees of an asset can not exceed the custody balance above the reported
+        /// liabilities.
+        #[weight = <T as Config>::WeightInfo::report_fees(fees.len() as u32)]
+        #[transactional]
+        pub fn report_fees(origin, fees: Vec<(AssetId, T::Balance)>) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            Self::ensure_allowed_enclave(&sender)?;
+            let payload = T::Hashing::hash_of(&(b"fees", &fees));
+            if Self::approve_payload(&sender, payload)? {
+                for (asset_id, amount) in fees {
+                    <AccruedFees<T>>::try_mutate(asset_id, |accrued| -> DispatchResult {
+                        *accrued = accrued.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+                        ensure!(*accrued <= Self::custody_surplus(asset_id), Error::<T>::FeesExceedSurplus);
+                        Ok(())
+                    })?;
+                    Self::deposit_event(RawEvent::FeesReported(sender.clone(), asset_id, amount));
+                }
+            }
+            Self::note_enclave_activity();
+            Ok(())
+        }
+
+        /// Report Liabilities
+        /// Replaces the total user balances per asset which the custody reserves must cover once
+        /// `ReleaseThreshold` enclaves reported the same liabilities under the next nonce
+        #[weight = <T as Config>::WeightInfo::report_liabilities(liabilities.len() as u32)]
+        #[transactional]
+        pub fn report_liabilities(origin, liabilities: Vec<(AssetId, T::Balance)>, nonce: u64) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            ensure!(
+                liabilities.len() <= T::MaxLiabilityAssets::get() as usize,
+                Error::<T>::TooManyLiabilityAssets
+            );
+            Self::ensure_not_exodus()?;
+            Self::ensure_allowed_enclave(&sender)?;
+            let mut assets: Vec<AssetId> = liabilities.iter().map(|(asset_id, _)| *asset_id).collect();
+            assets.sort();
+            assets.dedup();
+            ensure!(assets.len() == liabilities.len(), Error::<T>::DuplicateLiabilityAsset);
+            // Approvals of an older report can not replace newer liabilities
+            ensure!(
+                nonce == Self::liabilities_nonce().saturating_add(1),
+                Error::<T>::LiabilitiesNonceMismatch
+            );
+            let payload = T::Hashing::hash_of(&(b"liabilities", nonce, &liabilities));
+            if Self::approve_payload(&sender, payload)? {
+                let count = liabilities.len() as u32;
+                <Liabilities<T>>::put(liabilities);
+                LiabilitiesNonce::put(nonce);
+                Self::deposit_event(RawEvent::LiabilitiesReported(sender, count));
+            }
+            Self::note_enclave_activity();
+            Ok(())
+        }
+
+        /// Sweep Fees
+        /// Moves the accrued fees of an asset from the custody account to the treasury
+        #[weight = <T as Config>::WeightInfo::sweep_fees()]
+        pub fn sweep_fees(origin, asset_id: AssetId) -> DispatchResult {
+            ensure_signed(origin)?;
+            Self::ensure_not_exodus()?;
+            Self::ensure_not_paused(OcexOperation::Release, asset_id)?;
+            let amount = Self::accrued_fees(asset_id);
+            ensure!(!amount.is_zero(), Error::<T>::NoFeesToSweep);
+            ensure!(amount <= Self::custody_surplus(asset_id), Error::<T>::FeesExceedSurplus);
+            let treasury = <T as polkadex_fungible_assets::Config>::TreasuryAccountId::get();
+            <T as Config>::Currency::transfer(asset_id, &Self::get_account(), &treasury, amount)?;
+            <AccruedFees<T>>::remove(asset_id);
+            Self::deposit_event(RawEvent::FeesSwept(asset_id, amount, treasury));
+            Ok(())
+        }
+
+        /// Announce Successor
+        /// Starts handing over the release authority of the calling enclave to an allowed build
+        #[weight = <T as Config>::WeightInfo::announce_successor()]
+        pub fn announce_successor(origin, successor: MrEnclave) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            let mr_enclave = Self::ensure_allowed_enclave(&sender)?;
+            ensure!(Self::pending_handover().is_none(), Error::<T>::HandoverInProgress);
+            ensure!(successor != mr_enclave, Error::<T>::SuccessorIsCurrentBuild);
+            Self::ensure_allowed_mrenclave(&successor)?;
+            let deadline = <system::Module<T>>::block_number().saturating_add(T::HandoverTimeout::get());
+            <PendingHandover<T>>::put(EnclaveHandover {
+                old_enclave: sender.clone(),
+                old_build: mr_enclave,
+                successor,
+                deadline,
+            });
+            Self::deposit_event(RawEvent::HandoverAnnounced(sender, successor, deadline));
+            Ok(())
+        }
+
+        /// Acknowledge Handover
+        /// Called by the successor enclave with the last committed snapshot root to take over
+        #[weight = <T as Config>::WeightInfo::acknowledge_handover()]
+        pub fn acknowledge_handover(origin, state_root: T::Hash) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            let handover = Self::pending_handover().ok_or(Error::<T>::NoPendingHandover)?;
+            ensure!(!Self::is_retired(&sender), Error::<T>::EnclaveRetired);
+            let mr_enclave = Self::registered_mrenclave(&sender)?;
+            ensure!(mr_enclave == handover.successor, Error::<T>::NotTheSuccessor);
+            Self::ensure_allowed_mrenclave(&mr_enclave)?;
+            let latest_root = Self::latest_snapshot_epoch()
+                .and_then(Self::balance_snapshot)
+                .ok_or(Error::<T>::NoBalanceSnapshot)?;
+            ensure!(state_root == latest_root, Error::<T>::StateRootMismatch);
+            <PendingHandover<T>>::kill();
+            <RetiredEnclaves<T>>::insert(&handover.old_enclave, true);
+            <LiveEnclaves<T>>::mutate(|live| live.retain(|enclave| *enclave != handover.old_enclave));
+            // Other enclaves still running the old build get one handover timeout to upgrade
+            let now = <system::Module<T>>::block_number();
+            Self::limit_mrenclave_expiry(&handover.old_build, now.saturating_add(T::HandoverTimeout::get()));
+            Self::note_enclave_activity();
+            Self::deposit_event(RawEvent::HandoverCompleted(handover.old_enclave, sender, state_root));
+            Ok(())
+        }
+
+        /// Abort a handover, the announcing enclave keeps its release authority and the
+        /// successor build expires
+        #[weight = <T as Config>::WeightInfo::cancel_handover()]
+        pub fn cancel_handover(origin) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            let handover = Self::pending_handover().ok_or(Error::<T>::NoPendingHandover)?;
+            Self::roll_back_handover(handover);
+            Ok(())
+        }
+
+        /// Exodus Claim
+        /// Pays out a balance proven against the snapshot frozen by the exodus mode, adjusted by
+        /// the deposits and payouts made after the snapshot was committed and the open requests
+        #[weight = <T as Config>::WeightInfo::exodus_claim(proof.len() as u32)]
+        pub fn exodus_claim(origin, asset_id: AssetId, balance: T::Balance, proof: Vec<T::Hash>) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            let epoch = Self::exodus_mode().ok_or(Error::<T>::ExodusModeNotActive)?;
+            ensure!(!<ExodusClaims<T>>::get((epoch, who.clone()), asset_id), Error::<T>::ExodusAlreadyClaimed);
+            ensure!(Self::verify_balance_proof(epoch, &who, asset_id, balance, &proof), Error::<T>::InvalidBalanceProof);
+            let claim = Self::exodus_claimable(&who, asset_id, epoch, balance);
+            ensure!(!claim.is_zero(), Error::<T>::NothingToClaim);
+            <T as Config>::Currency::transfer(asset_id, &Self::get_account(), &who, claim)?;
+            <ExodusClaims<T>>::insert((epoch, who.clone()), asset_id, true);
+            Self::reduce_account_deposits(&who, asset_id, claim);
+            Self::deposit_event(RawEvent::ExodusClaimed(epoch, who, asset_id, claim));
+            Ok(())
+        }
+
+        /// Leave the exodus mode once a healthy enclave is registered again
+        #[weight = <T as Config>::WeightInfo::exit_exodus_mode()]
+        pub fn exit_exodus_mode(origin, enclave: T::AccountId) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(Self::exodus_mode().is_some(), Error::<T>::ExodusModeNotActive);
+            Self::ensure_allowed_enclave(&enclave)?;
+            ExodusMode::kill();
+            Self::note_enclave_activity();
+            Self::deposit_event(RawEvent::ExodusModeDeactivated(enclave));
+            Ok(())
+        }
+
+        /// Set the deposit rules of an asset
+        #[weight = <T as Config>::WeightInfo::set_asset_config()]
+        pub fn set_asset_config(origin, asset_id: AssetId, config: AssetDepositConfig<T::Balance>) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            <AssetConfigs<T>>::insert(asset_id, config);
+            Self::deposit_event(RawEvent::AssetConfigUpdated(asset_id));
+            Ok(())
+        }
+
+        /// Set the number of distinct enclaves which must approve a release
+        #[weight = <T as Config>::WeightInfo::set_release_threshold()]
+        pub fn set_release_threshold(origin, threshold: u32) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(threshold > 0, Error::<T>::InvalidReleaseThreshold);
+            ReleaseThreshold::put(threshold);
+            Self::deposit_event(RawEvent::ReleaseThresholdUpdated(threshold));
+            Ok(())
+        }
+
+        /// Set or clear the outflow limit of an asset
+        #[weight = <T as Config>::WeightInfo::set_outflow_limit()]
+        pub fn set_outflow_limit(origin, asset_id: AssetId, limit: Option<OutflowLimit<T::Balance, T::BlockNumber>>) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            match limit {
+                Some(limit) => <OutflowLimits<T>>::insert(asset_id, limit),
+                None => <OutflowLimits<T>>::remove(asset_id),
+            }
+            Self::deposit_event(RawEvent::OutflowLimitUpdated(asset_id));
+            Ok(())
+        }
+
+        /// Pay out a release held back by the outflow limit once its delay has passed
+        #[weight = <T as Config>::WeightInfo::approve_queued_release()]
+        pub fn approve_queued_release(origin, withdrawal_id: WithdrawalId) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            Self::ensure_not_exodus()?;
+            let queued = Self::queued_release(withdrawal_id).ok_or(Error::<T>::ReleaseNotQueued)?;
+            ensure!(<system::Module<T>>::block_number() >= queued.unlock_at, Error::<T>::ReleaseDelayNotElapsed);
+            let request = Self::withdrawals(withdrawal_id).ok_or(Error::<T>::WithdrawalNotFound)?;
+            Self::ensure_not_paused(OcexOperation::Release, request.asset_id)?;
+            Self::pay_out(withdrawal_id, request, queued.enclave, queued.mr_enclave)?;
+            <QueuedReleases<T>>::remove(withdrawal_id);
+            Ok(())
+        }
+
+        /// Refuse a release held back by the outflow limit
+        #[weight = <T as Config>::WeightInfo::cancel_queued_release()]
+        pub fn cancel_queued_release(origin, withdrawal_id: WithdrawalId) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(<QueuedReleases<T>>::contains_key(withdrawal_id), Error::<T>::ReleaseNotQueued);
+            let request = Self::withdrawals(withdrawal_id).ok_or(Error::<T>::WithdrawalNotFound)?;
+            Self::close_withdrawal(withdrawal_id, &request, WithdrawalStatus::Rejected(<system::Module<T>>::block_number()));
+            <QueuedReleases<T>>::remove(withdrawal_id);
+            Self::deposit_event(RawEvent::QueuedReleaseCancelled(withdrawal_id));
+            Ok(())
+        }
+
+        /// Set Challenge Window
+        /// Delays releases by `window` blocks during which they can be challenged, or pays them
+        /// out immediately if `None`
+        #[weight = <T as Config>::WeightInfo::set_challenge_window()]
+        pub fn set_challenge_window(origin, window: Option<T::BlockNumber>) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            match window {
+                Some(window) => {
+                    ensure!(!window.is_zero(), Error::<T>::InvalidChallengeWindow);
+                    <ChallengeWindow<T>>::put(window);
+                }
+                None => <ChallengeWindow<T>>::kill(),
+            }
+            Self::deposit_event(RawEvent::ChallengeWindowUpdated(window));
+            Ok(())
+        }
+
+        /// Challenge Release
+        /// Halts a delayed release until governance decides on it, reserving `ChallengeBond`
+        /// PDEX of the watcher
+        #[weight = <T as Config>::WeightInfo::challenge_release()]
+        pub fn challenge_release(origin, withdrawal_id: WithdrawalId) -> DispatchResult {
+            let watcher: T::AccountId = ensure_signed(origin)?;
+            let bond = T::ChallengeBond::get();
+            <DelayedReleases<T>>::try_mutate(withdrawal_id, |delayed| -> DispatchResult {
+                let delayed = delayed.as_mut().ok_or(Error::<T>::ReleaseNotDelayed)?;
+                ensure!(delayed.challenge.is_none(), Error::<T>::ReleaseAlreadyChallenged);
+                ensure!(
+                    <system::Module<T>>::block_number() < delayed.execute_at,
+                    Error::<T>::ChallengeWindowClosed
+                );
+                <T as Config>::Currency::reserve(AssetId::POLKADEX, &watcher, bond)?;
+                delayed.challenge = Some(ReleaseChallenge {
+                    watcher: watcher.clone(),
+                    bond,
+                });
+                Ok(())
+            })?;
+            Self::set_withdrawal_status(withdrawal_id, WithdrawalStatus::Challenged);
+            Self::deposit_event(RawEvent::ReleaseChallenged(withdrawal_id, watcher, bond));
+            Ok(())
+        }
+
+        /// Cancel Delayed Release
+        /// Refuses a delayed release, returning the bond of a watcher who challenged it
+        #[weight = <T as Config>::WeightInfo::cancel_delayed_release()]
+        pub fn cancel_delayed_release(origin, withdrawal_id: WithdrawalId) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            let delayed = Self::delayed_release(withdrawal_id).ok_or(Error::<T>::ReleaseNotDelayed)?;
+            let request = Self::withdrawals(withdrawal_id).ok_or(Error::<T>::WithdrawalNotFound)?;
+            if let Some(challenge) = delayed.challenge {
+                <T as Config>::Currency::unreserve(AssetId::POLKADEX, &challenge.watcher, challenge.bond);
+            }
+            <DelayedReleases<T>>::remove(withdrawal_id);
+            Self::close_withdrawal(withdrawal_id, &request, WithdrawalStatus::Rejected(<system::Module<T>>::block_number()));
+            Self::deposit_event(RawEvent::DelayedReleaseCancelled(withdrawal_id));
+            Ok(())
+        }
+
+        /// Dismiss Challenge
+        /// Slashes the bond of a frivolous challenge to the treasury and resumes the release
+        #[weight = <T as Config>::WeightInfo::dismiss_challenge()]
+        #[transactional]
+        pub fn dismiss_challenge(origin, withdrawal_id: WithdrawalId) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            let mut delayed = Self::delayed_release(withdrawal_id).ok_or(Error::<T>::ReleaseNotDelayed)?;
+            let challenge = delayed.challenge.take().ok_or(Error::<T>::ReleaseNotChallenged)?;
+            let treasury = <T as polkadex_fungible_assets::Config>::TreasuryAccountId::get();
+            <T as Config>::Currency::repatriate_reserved(
+                AssetId::POLKADEX,
+                &challenge.watcher,
+                &treasury,
+                challenge.bond,
+                BalanceStatus::Free,
+            )?;
+            // A release whose window passed while it was challenged is paid out in the next block
+            let now = <system::Module<T>>::block_number();
+            if delayed.execute_at <= now {
+                delayed.execute_at = now.saturating_add(One::one());
+                <DelayedReleaseSchedule<T>>::append(delayed.execute_at, withdrawal_id);
+            }
+            Self::set_withdrawal_status(withdrawal_id, WithdrawalStatus::Delayed(delayed.execute_at));
+            <DelayedReleases<T>>::insert(withdrawal_id, delayed);
+            Self::deposit_event(RawEvent::ChallengeDismissed(withdrawal_id, challenge.watcher, challenge.bond));
+            Ok(())
+        }
+
+        /// Execute Delayed Release
+        /// Pays out an unchallenged delayed release which could not be paid out in its block
+        #[weight = <T as Config>::WeightInfo::execute_delayed_release()]
+        pub fn execute_delayed_release(origin, withdrawal_id: WithdrawalId) -> DispatchResult {
+            ensure_signed(origin)?;
+            Self::pay_out_delayed_release(withdrawal_id)
+        }
+
+        /// Pause or resume an operation, for one asset or for all assets
+        #[weight = <T as Config>::WeightInfo::set_paused()]
+        pub fn set_paused(origin, operation: OcexOperation, asset_id: Option<AssetId>, paused: bool) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            match asset_id {
+                Some(asset_id) => AssetPause::insert(operation, asset_id, paused),
+                None => GlobalPause::insert(operation, paused),
+            }
+            if paused {
+                Self::deposit_event(RawEvent::OperationPaused(operation, asset_id));
+            } else {
+                Self::deposit_event(RawEvent::OperationResumed(operation, asset_id));
+            }
+            Ok(())
+        }
+
+        /// Allow an enclave build to release funds
+        #[weight = <T as Config>::WeightInfo::allow_mrenclave()]
+        pub fn allow_mrenclave(origin, mr_enclave: MrEnclave, expires_at: Option<T::BlockNumber>) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(!<AllowedMrEnclaves<T>>::contains_key(&mr_enclave), Error::<T>::MrEnclaveAlreadyAllowed);
+            let info = MrEnclaveInfo {
+                allowed_at: <system::Module<T>>::block_number(),
+                expires_at,
+            };
+            <AllowedMrEnclaves<T>>::insert(mr_enclave, info);
+            Self::deposit_event(RawEvent::MrEnclaveAllowed(mr_enclave));
+            if let Some(expires_at) = expires_at {
+                Self::deposit_event(RawEvent::MrEnclaveExpirySet(mr_enclave, expires_at));
+            }
+            Ok(())
+        }
+
+        /// Remove an enclave build from the allowlist
+        #[weight = <T as Config>::WeightInfo::remove_mrenclave()]
+        pub fn remove_mrenclave(origin, mr_enclave: MrEnclave) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(<AllowedMrEnclaves<T>>::contains_key(&mr_enclave), Error::<T>::MrEnclaveNotAllowed);
+            <AllowedMrEnclaves<T>>::remove(mr_enclave);
+            Self::deposit_event(RawEvent::MrEnclaveRemoved(mr_enclave));
+            Ok(())
+        }
+
+        /// Schedule the expiry of an allowed enclave build
+        #[weight = <T as Config>::WeightInfo::expire_mrenclave()]
+        pub fn expire_mrenclave(origin, mr_enclave: MrEnclave, expires_at: T::BlockNumber) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            <AllowedMrEnclaves<T>>::try_mutate(&mr_enclave, |info| {
+                let info = info.as_mut().ok_or(Error::<T>::MrEnclaveNotAllowed)?;
+                info.expires_at = Some(expires_at);
+                Self::deposit_event(RawEvent::MrEnclaveExpirySet(mr_enclave, expires_at));
+                Ok(())
+            })
+        }
+
+        /// Add Static Enclave
+        /// Lets `enclave` act as an enclave running `mr_enclave` without registry attestation,
+        /// if the enclave authority uses static keys
+        #[weight = <T as Config>::WeightInfo::add_static_enclave()]
+        pub fn add_static_enclave(origin, enclave: T::AccountId, mr_enclave: MrEnclave) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(T::EnclaveAuthority::accepts_static_keys(), Error::<T>::StaticEnclavesNotSupported);
+            ensure!(!<StaticEnclaveKeys<T>>::contains_key(&enclave), Error::<T>::StaticEnclaveExists);
+            <StaticEnclaveKeys<T>>::insert(&enclave, mr_enclave);
+            Self::deposit_event(RawEvent::StaticEnclaveAdded(enclave, mr_enclave));
+            Ok(())
+        }
+
+        /// Remove Static Enclave
+        #[weight = <T as Config>::WeightInfo::remove_static_enclave()]
+        pub fn remove_static_enclave(origin, enclave: T::AccountId) -> DispatchResult {
+            <T as Config>::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(T::EnclaveAuthority::accepts_static_keys(), Error::<T>::StaticEnclavesNotSupported);
+            ensure!(<StaticEnclaveKeys<T>>::contains_key(&enclave), Error::<T>::StaticEnclaveNotFound);
+            <StaticEnclaveKeys<T>>::remove(&enclave);
+            Self::deposit_event(RawEvent::StaticEnclaveRemoved(enclave));
+            Ok(())
+        }
+
+        /// Register Trading Key
+        /// Proposes `key` for the calling main account, it is registered once the key confirms it
+        #[weight = <T as Config>::WeightInfo::register_trading_key()]
+        pub fn register_trading_key(origin, key: T::TradingKey) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            let key_account = key.clone().into_account();
+            Self::ensure_can_register_key(&sender, &key_account)?;
+            <PendingTradingKeys<T>>::insert(&sender, key);
+            Self::deposit_event(RawEvent::TradingKeyProposed(sender, key_account));
+            Ok(())
+        }
+
+        /// Confirm Trading Key
+        /// Signed by the key proposed by `main`, proves control of it and registers it
+        #[weight = <T as Config>::WeightInfo::confirm_trading_key()]
+        pub fn confirm_trading_key(origin, main: T::AccountId) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            let key = Self::pending_trading_key(&main)
+                .filter(|key| key.clone().into_account() == sender)
+                .ok_or(Error::<T>::TradingKeyNotProposed)?;
+            Self::ensure_can_register_key(&main, &sender)?;
+            <TradingKeys<T>>::mutate(&main, |keys| keys.push(key));
+            <PendingTradingKeys<T>>::remove(&main);
+            <TradingKeyOwner<T>>::insert(&sender, &main);
+            Self::deposit_event(RawEvent::TradingKeyRegistered(main, sender));
+            Ok(())
+        }
+
+        /// Revoke Trading Key
+        /// Stops the enclave from accepting orders signed by `key`
+        #[weight = <T as Config>::WeightInfo::revoke_trading_key()]
+        pub fn revoke_trading_key(origin, key: T::TradingKey) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            let key_account = key.clone().into_account();
+            ensure!(Self::trading_key_owner(&key_account) == Some(sender.clone()), Error::<T>::TradingKeyNotFound);
+            <TradingKeys<T>>::mutate_exists(&sender, |keys| {
+                if let Some(registered) = keys {
+                    registered.retain(|registered| *registered != key);
+                    if registered.is_empty() {
+                        *keys = None;
+                    }
+                }
+            });
+            <TradingKeyOwner<T>>::remove(&key_account);
+            Self::deposit_event(RawEvent::TradingKeyRevoked(sender, key_account));
+            Ok(())
+        }
+
+        /// Register Trader
+        /// Reserves `stake` PDEX so the whitelisted calls of the sender and its trading keys are feeless
+        #[weight = <T as Config>::WeightInfo::register_trader()]
+        pub fn register_trader(origin, stake: T::Balance) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            ensure!(!<TradingKeyOwner<T>>::contains_key(&sender), Error::<T>::NotAMainAccount);
+            ensure!(!<TraderStakes<T>>::contains_key(&sender), Error::<T>::TraderAlreadyRegistered);
+            ensure!(stake >= T::MinTraderStake::get(), Error::<T>::TraderStakeTooLow);
+            <T as Config>::Currency::reserve(AssetId::POLKADEX, &sender, stake)?;
+            <TraderStakes<T>>::insert(&sender, stake);
+            Self::deposit_event(RawEvent::TraderRegistered(sender, stake));
+            Ok(())
+        }
+
+        /// Unregister Trader
+        /// Transactions of the sender pay fees again, its stake unbonds for `TraderUnbondingPeriod`
+        /// blocks so it can not back the feeless quota of another account in the same window
+        #[weight = <T as Config>::WeightInfo::unregister_trader()]
+        pub fn unregister_trader(origin) -> DispatchResult {
+            let sender: T::AccountId = ensure_signed(origin)?;
+            let stake = <TraderStakes<T>>::take(&sender).ok_or(Error::<T>::TraderNotRegistered)?;
+            let unlock_at = <system::Module<T>>::block_number()
+                .saturating_add(T::TraderUnbondingPeriod::get());
+            // A stake still unbonding from an earlier registration waits for the new unlock block
+            let unbonding = Self::unbonding_trader_stake(&sender)
+                .map_or(stake, |(earlier, _)| earlier.saturating_add(stake));
+            <UnbondingTraderStakes<T>>::insert(&sender, (unbonding, unlock_at));
+            Self::deposit_event(RawEvent::TraderUnregistered(sender, stake, unlock_at));
+            Ok(())
+        }
+
+        /// Withdraw Unbonded Trader Stake
+        /// Releases the stake of the sender once its unbonding period is over
+        #[weight = <T as Config>::WeightInfo::withdraw_unbonded_stake()]
+        pub fn withdraw_unbonded_stake(origin) -> DispatchResult {
             let sender: T::AccountId = ensure_signed(origin)?;
-            ensure!(pallet_substratee_registry::EnclaveIndex::<T>::contains_key(&sender), Error::<T>::NotARegisteredEnclave);
-            // TODO: Check if the latest MRENCLAVE is registered by this sender
-            // TODO: Handle software updated to enclave
-            <T as Config>::Currency::transfer(asset_id, &Self::get_account(), &to, amount)?;
+            let (stake, unlock_at) = Self::unbonding_trader_stake(&sender).ok_or(Error::<T>::NoUnbondingStake)?;
+            ensure!(<system::Module<T>>::block_number() >= unlock_at, Error::<T>::StakeStillUnbonding);
+            <UnbondingTraderStakes<T>>::remove(&sender);
+            <T as Config>::Currency::unreserve(AssetId::POLKADEX, &sender, stake);
+            Self::deposit_event(RawEvent::TraderStakeWithdrawn(sender, stake));
             Ok(())
         }
 
         /// Withdraw
         /// It helps to notify enclave about sender's intend to withdraw via on-chain
-        #[weight = 10000]
+        #[weight = <T as Config>::WeightInfo::withdraw()]
         pub fn withdraw(origin, asset_id:  AssetId, to: T::AccountId,amount: T::Balance) -> DispatchResult{
             let sender: T::AccountId = ensure_signed(origin)?;
-            Self::deposit_event(RawEvent::TokenWithdrawn(asset_id, to, amount));
+            ensure!(!<TradingKeyOwner<T>>::contains_key(&sender), Error::<T>::TradingKeyCannotWithdraw);
+            ensure!(!<TradingKeyOwner<T>>::contains_key(&to), Error::<T>::TradingKeyDestination);
+            ensure!(!amount.is_zero(), Error::<T>::WithdrawalAmountZero);
+            Self::ensure_not_exodus()?;
+            Self::ensure_not_paused(OcexOperation::Withdraw, asset_id)?;
+            // Trades move balances between accounts, so solvency is checked by the enclave
+            let pending = Self::pending_withdrawal_amount(&sender, asset_id).saturating_add(amount);
+            let withdrawal_id = Self::next_withdrawal_id();
+            let next_id = withdrawal_id.checked_add(1).ok_or(Error::<T>::Overflow)?;
+            let request = WithdrawalRequest {
+                account: sender.clone(),
+                asset_id,
+                amount,
+                destination: to.clone(),
+                block: <system::Module<T>>::block_number(),
+                status: WithdrawalStatus::Pending,
+            };
+            <Withdrawals<T>>::insert(withdrawal_id, request);
+            <PendingWithdrawals<T>>::insert(&sender, withdrawal_id, true);
+            <PendingWithdrawalAmounts<T>>::insert(&sender, asset_id, pending);
+            NextWithdrawalId::put(next_id);
+            Self::deposit_event(RawEvent::WithdrawalRequested(withdrawal_id, asset_id, sender, amount, to));
             Ok(())
         }
 
@@ -110,6 +1359,738 @@ impl<T: Config> Module<T> {
     pub fn get_account() -> T::AccountId {
         T::OcexId::get().into_account()
     }
+
+    /// Returns the MRENCLAVE of `enclave` if it is registered and its build is currently allowed
+    ///
+    /// Enclaves which handed over their authority are refused, and so are successor builds
+    /// until they acknowledged the handover.
+    pub fn ensure_allowed_enclave(enclave: &T::AccountId) -> Result<MrEnclave, Error<T>> {
+        ensure!(!Self::is_retired(enclave), Error::<T>::EnclaveRetired);
+        let mr_enclave = Self::registered_mrenclave(enclave)?;
+        Self::ensure_allowed_mrenclave(&mr_enclave)?;
+        if let Some(handover) = Self::pending_handover() {
+            ensure!(
+                handover.successor != mr_enclave,
+                Error::<T>::HandoverNotAcknowledged
+            );
+        }
+        Ok(mr_enclave)
+    }
+
+    /// Returns the MRENCLAVE `enclave` registered with
+    fn registered_mrenclave(enclave: &T::AccountId) -> Result<MrEnclave, Error<T>> {
+        T::EnclaveAuthority::mr_enclave(enclave).ok_or(Error::<T>::NotARegisteredEnclave)
+    }
+
+    /// Checks that governance currently accepts `mr_enclave`
+    fn ensure_allowed_mrenclave(mr_enclave: &MrEnclave) -> Result<(), Error<T>> {
+        let info = <AllowedMrEnclaves<T>>::get(mr_enclave).ok_or(Error::<T>::MrEnclaveNotAllowed)?;
+        ensure!(
+            !info.is_expired(&<system::Module<T>>::block_number()),
+            Error::<T>::MrEnclaveExpired
+        );
+        Ok(())
+    }
+
+    /// All listed markets
+    pub fn markets() -> Vec<(TradingPair, MarketInfo<T::Balance>)> {
+        <Markets<T>>::iter().collect()
+    }
+
+    /// Whether `key_account` may become a trading key of `main`
+    fn ensure_can_register_key(main: &T::AccountId, key_account: &T::AccountId) -> DispatchResult {
+        ensure!(!<TradingKeyOwner<T>>::contains_key(main), Error::<T>::NotAMainAccount);
+        ensure!(
+            key_account != main && !<TradingKeys<T>>::contains_key(key_account),
+            Error::<T>::NotAMainAccount
+        );
+        ensure!(!<TradingKeyOwner<T>>::contains_key(key_account), Error::<T>::TradingKeyInUse);
+        ensure!(
+            Self::trading_keys(main).len() < T::MaxTradingKeys::get() as usize,
+            Error::<T>::TooManyTradingKeys
+        );
+        Ok(())
+    }
+
+    /// Main account of `account` if it is a trading key, otherwise `account` itself
+    pub fn main_account(account: &T::AccountId) -> T::AccountId {
+        Self::trading_key_owner(account).unwrap_or_else(|| account.clone())
+    }
+
+    /// Registered trader whose quota covers `call` from `who` without fees
+    ///
+    /// Trading keys use the quota of their main account. Returns `None` once the trader sent
+    /// `MaxFeelessPerBlock` feeless transactions in this block or used up its stake quota.
+    pub fn feeless_trader(who: &T::AccountId, call: &<T as system::Config>::Call) -> Option<T::AccountId> {
+        if !T::FeelessCalls::filter(call) {
+            return None;
+        }
+        let trader = Self::main_account(who);
+        let stake = Self::trader_stake(&trader)?;
+        let usage = Self::current_feeless_usage(&trader);
+        if usage.in_block < T::MaxFeelessPerBlock::get() && usage.in_window < Self::feeless_quota(stake) {
+            Some(trader)
+        } else {
+            None
+        }
+    }
+
+    /// Counts a feeless transaction against the quota of `trader`
+    pub fn note_feeless_transaction(trader: &T::AccountId) {
+        let mut usage = Self::current_feeless_usage(trader);
+        usage.in_window = usage.in_window.saturating_add(1);
+        usage.in_block = usage.in_block.saturating_add(1);
+        <FeelessUsages<T>>::insert(trader, usage);
+    }
+
+    /// Tag provided by the next feeless transaction of `trader`
+    ///
+    /// It changes once a feeless transaction of the trader is included, so the transaction pool
+    /// holds one feeless transaction per trader at a time instead of any number of them. The
+    /// quota is checked against the chain state only, a trader with one feeless transaction
+    /// left could otherwise fill the pool with free transactions of which all but one fail
+    /// `pre_dispatch`. Feeless transactions pay nothing for being rejected, so the pool would
+    /// carry that spam for free. Trading keys share the tag of their main account, further
+    /// transactions of a trader in the same block pay fees.
+    pub fn feeless_tag(trader: &T::AccountId) -> Vec<u8> {
+        let usage = Self::current_feeless_usage(trader);
+        (b"feeless", trader, usage.window_start, usage.in_window).encode()
+    }
+
+    /// Number of feeless transactions per window backed by `stake`
+    pub fn feeless_quota(stake: T::Balance) -> u32 {
+        stake
+            .checked_div(&T::StakePerFeelessTransaction::get())
+            .map(|quota| quota.unique_saturated_into())
+            .unwrap_or(0)
+    }
+
+    /// Feeless usage of `trader` with the counters of elapsed windows and blocks reset
+    fn current_feeless_usage(trader: &T::AccountId) -> FeelessUsage<T::BlockNumber> {
+        let now = <system::Module<T>>::block_number();
+        let mut usage = Self::feeless_usage(trader);
+        if usage.window_start.saturating_add(T::FeelessWindow::get()) <= now || usage.in_window == 0 {
+            usage.window_start = now;
+            usage.in_window = 0;
+        }
+        if usage.block != now {
+            usage.block = now;
+            usage.in_block = 0;
+        }
+        usage
+    }
+
+    /// Free balances of the custody account per asset
+    ///
+    /// PDEX is held by the native currency rather than `orml_tokens`, so it is read through
+    /// `Currency`.
+    pub fn custody_balances() -> Vec<(AssetId, T::Balance)> {
+        let custody = Self::get_account();
+        let mut balances: Vec<(AssetId, T::Balance)> =
+            orml_tokens::Accounts::<T>::iter_prefix(&custody)
+                .filter(|(asset_id, _)| *asset_id != AssetId::POLKADEX)
+                .map(|(asset_id, account)| (asset_id, account.free))
+                .collect();
+        let native = <T as Config>::Currency::free_balance(AssetId::POLKADEX, &custody);
+        if !native.is_zero() {
+            balances.push((AssetId::POLKADEX, native));
+        }
+        balances
+    }
+
+    /// Withdrawal requests of `account` which are not settled or rejected yet
+    pub fn pending_withdrawals_of(account: &T::AccountId) -> Vec<(WithdrawalId, WithdrawalRequestOf<T>)> {
+        <PendingWithdrawals<T>>::iter_prefix(account)
+            .filter_map(|(withdrawal_id, _)| {
+                Self::withdrawals(withdrawal_id).map(|request| (withdrawal_id, request))
+            })
+            .collect()
+    }
+
+    /// Enclaves known to the enclave authority, with whether they may release funds and are live
+    pub fn registered_enclaves() -> Vec<EnclaveStatus<T::AccountId>> {
+        let live = Self::live_enclaves();
+        T::EnclaveAuthority::enclaves()
+            .into_iter()
+            .map(|(account, mr_enclave)| EnclaveStatus {
+                allowed: Self::ensure_allowed_enclave(&account).is_ok(),
+                live: live.contains(&account),
+                mr_enclave,
+                account,
+            })
+            .collect()
+    }
+
+    /// Custody reserves against the reported liabilities per asset
+    pub fn reserve_ratios() -> Vec<(AssetId, ReserveStatus<T::Balance>)> {
+        let custody = Self::get_account();
+        Self::liabilities()
+            .into_iter()
+            .map(|(asset_id, liabilities)| {
+                let reserves = <T as Config>::Currency::free_balance(asset_id, &custody);
+                let numerator: u128 = reserves.unique_saturated_into();
+                let denominator: u128 = liabilities.unique_saturated_into();
+                let status = ReserveStatus {
+                    reserves,
+                    liabilities,
+                    ratio: FixedU128::checked_from_rational(numerator, denominator),
+                };
+                (asset_id, status)
+            })
+            .collect()
+    }
+
+    /// Paused operations, for one asset or for all assets if the asset is `None`
+    pub fn paused_operations() -> Vec<(OcexOperation, Option<AssetId>)> {
+        GlobalPause::iter()
+            .filter(|(_, paused)| *paused)
+            .map(|(operation, _)| (operation, None))
+            .chain(
+                AssetPause::iter()
+                    .filter(|(_, _, paused)| *paused)
+                    .map(|(operation, asset_id, _)| (operation, Some(asset_id))),
+            )
+            .collect()
+    }
+
+    fn ensure_valid_market(
+        market: &TradingPair,
+        tick_size: T::Balance,
+        lot_size: T::Balance,
+    ) -> Result<(), Error<T>> {
+        ensure!(
+            market.base != market.quote && !tick_size.is_zero() && !lot_size.is_zero(),
+            Error::<T>::InvalidMarket
+        );
+        Ok(())
+    }
+
+    /// Leaf of the balance snapshot merkle tree
+    pub fn balance_leaf(account: &T::AccountId, asset_id: AssetId, balance: T::Balance) -> T::Hash {
+        T::Hashing::hash_of(&(account, asset_id, balance))
+    }
+
+    /// Checks that `balance` of `account` is included in the snapshot of `epoch`
+    ///
+    /// Sibling hashes are combined in sorted order, so the proof does not need leaf positions.
+    pub fn verify_balance_proof(
+        epoch: SnapshotEpoch,
+        account: &T::AccountId,
+        asset_id: AssetId,
+        balance: T::Balance,
+        proof: &[T::Hash],
+    ) -> bool {
+        let root = match Self::balance_snapshot(epoch) {
+            Some(root) => root,
+            None => return false,
+        };
+        let leaf = Self::balance_leaf(account, asset_id, balance);
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            if node <= *sibling {
+                T::Hashing::hash_of(&(node, sibling))
+            } else {
+                T::Hashing::hash_of(&(sibling, node))
+            }
+        });
+        computed == root
+    }
+
+    /// Drops enclaves whose last heartbeat is older than `HeartbeatInterval`
+    fn expire_heartbeats(now: T::BlockNumber) -> Weight {
+        let live = Self::live_enclaves();
+        let mut weight = T::DbWeight::get().reads(1 + live.len() as Weight);
+        let (still_live, stale): (Vec<_>, Vec<_>) = live.into_iter().partition(|enclave| {
+            Self::enclave_heartbeat(enclave)
+                .map(|last| now.saturating_sub(last) <= T::HeartbeatInterval::get())
+                .unwrap_or(false)
+        });
+        if !stale.is_empty() {
+            for enclave in stale {
+                let last = Self::enclave_heartbeat(&enclave).unwrap_or_default();
+                Self::deposit_event(RawEvent::EnclaveStale(enclave, last));
+            }
+            <LiveEnclaves<T>>::put(still_live);
+            weight = weight.saturating_add(T::DbWeight::get().writes(1));
+        }
+        weight
+    }
+
+    /// Starts the exodus mode once the enclave was inactive for longer than `ExodusTimeout`
+    fn check_exodus_timeout(now: T::BlockNumber) -> Weight {
+        if Self::exodus_mode().is_some() {
+            return T::DbWeight::get().reads(1);
+        }
+        let latest_epoch = match Self::latest_snapshot_epoch() {
+            Some(epoch) => epoch,
+            // Nothing could be claimed without a snapshot
+            None => return T::DbWeight::get().reads(2),
+        };
+        if now.saturating_sub(Self::last_enclave_activity()) > T::ExodusTimeout::get() {
+            ExodusMode::put(latest_epoch);
+            Self::deposit_event(RawEvent::ExodusModeActivated(latest_epoch));
+            return T::DbWeight::get().reads_writes(3, 1);
+        }
+        T::DbWeight::get().reads(3)
+    }
+
+    /// Checks the deposit rules of `asset_id` and returns the new net deposit of `who`
+    fn ensure_deposit_allowed(
+        who: &T::AccountId,
+        asset_id: AssetId,
+        amount: T::Balance,
+    ) -> Result<T::Balance, Error<T>> {
+        let config = Self::asset_config(asset_id).ok_or(Error::<T>::AssetNotEnabled)?;
+        ensure!(config.enabled, Error::<T>::AssetNotEnabled);
+        match asset_id {
+            AssetId::CHAINSAFE(_) | AssetId::TOKEN(_) => ensure!(
+                polkadex_fungible_assets::Module::<T>::get_assetinfo(asset_id).is_verified,
+                Error::<T>::AssetNotVerified
+            ),
+            AssetId::POLKADEX | AssetId::DOT => {}
+        }
+        ensure!(
+            !amount.is_zero() && amount >= config.min_deposit,
+            Error::<T>::DepositBelowMinimum
+        );
+        if let Some(max_deposit) = config.max_deposit {
+            ensure!(amount <= max_deposit, Error::<T>::DepositAboveMaximum);
+        }
+        let total = Self::account_deposits(who, asset_id).saturating_add(amount);
+        if let Some(max_per_account) = config.max_per_account {
+            ensure!(total <= max_per_account, Error::<T>::AccountDepositCapExceeded);
+        }
+        Ok(total)
+    }
+
+    fn reduce_account_deposits(who: &T::AccountId, asset_id: AssetId, amount: T::Balance) {
+        <AccountDeposits<T>>::mutate(who, asset_id, |deposited| {
+            *deposited = deposited.saturating_sub(amount)
+        });
+    }
+
+    /// Adds a deposit or payout to the flows of `who` since the latest balance snapshot
+    fn note_snapshot_flow(
+        who: &T::AccountId,
+        asset_id: AssetId,
+        deposited: T::Balance,
+        withdrawn: T::Balance,
+    ) {
+        let epoch = Self::latest_snapshot_epoch();
+        <SnapshotFlows<T>>::mutate(who, asset_id, |flow| {
+            if flow.epoch != epoch {
+                *flow = SnapshotFlow { epoch, ..Default::default() };
+            }
+            flow.deposited = flow.deposited.saturating_add(deposited);
+            flow.withdrawn = flow.withdrawn.saturating_add(withdrawn);
+        });
+    }
+
+    /// Balance of `who` in the snapshot of `epoch` plus the later deposits, minus the later payouts
+    /// and the open withdrawal requests
+    ///
+    /// Open requests can still be paid once the exodus mode ends, so they are not claimed twice.
+    fn exodus_claimable(
+        who: &T::AccountId,
+        asset_id: AssetId,
+        epoch: SnapshotEpoch,
+        balance: T::Balance,
+    ) -> T::Balance {
+        let flow = Self::snapshot_flow(who, asset_id);
+        let balance = if flow.epoch == Some(epoch) {
+            balance.saturating_add(flow.deposited).saturating_sub(flow.withdrawn)
+        } else {
+            balance
+        };
+        balance.saturating_sub(Self::pending_withdrawal_amount(who, asset_id))
+    }
+
+    /// Custody balance of `asset_id` which is not owed to users according to the reported
+    /// liabilities, nothing counts as surplus while no liability is reported for the asset
+    fn custody_surplus(asset_id: AssetId) -> T::Balance {
+        match Self::liabilities().into_iter().find(|(asset, _)| *asset == asset_id) {
+            Some((_, owed)) => <T as Config>::Currency::free_balance(asset_id, &Self::get_account())
+                .saturating_sub(owed),
+            None => Zero::zero(),
+        }
+    }
+
+    /// Checks that governance has not halted `operation` for `asset_id` or for all assets
+    pub fn ensure_not_paused(operation: OcexOperation, asset_id: AssetId) -> Result<(), Error<T>> {
+        ensure!(
+            !Self::globally_paused(operation) && !Self::asset_paused(operation, asset_id),
+            Error::<T>::OperationPaused
+        );
+        Ok(())
+    }
+
+    /// Rolls back a handover whose successor did not take over before the deadline
+    fn check_handover_deadline(now: T::BlockNumber) -> Weight {
+        match Self::pending_handover() {
+            Some(handover) if now > handover.deadline => {
+                Self::roll_back_handover(handover);
+                T::DbWeight::get().reads_writes(2, 2)
+            }
+            _ => T::DbWeight::get().reads(1),
+        }
+    }
+
+    /// Drops a pending handover and lets the successor build expire, so it can not release
+    /// without a new announcement and allowlisting by governance
+    fn roll_back_handover(handover: EnclaveHandover<T::AccountId, T::BlockNumber>) {
+        <PendingHandover<T>>::kill();
+        Self::limit_mrenclave_expiry(&handover.successor, <system::Module<T>>::block_number());
+        Self::deposit_event(RawEvent::HandoverRolledBack(handover.old_enclave, handover.successor));
+    }
+
+    /// Moves the expiry of an allowed build forward to `expires_at`, an earlier expiry is kept
+    fn limit_mrenclave_expiry(mr_enclave: &MrEnclave, expires_at: T::BlockNumber) {
+        <AllowedMrEnclaves<T>>::mutate(mr_enclave, |info| {
+            if let Some(info) = info {
+                if info.expires_at.map_or(true, |current| current > expires_at) {
+                    info.expires_at = Some(expires_at);
+                    Self::deposit_event(RawEvent::MrEnclaveExpirySet(*mr_enclave, expires_at));
+                }
+            }
+        });
+    }
+
+    fn ensure_not_exodus() -> Result<(), Error<T>> {
+        ensure!(Self::exodus_mode().is_none(), Error::<T>::ExodusModeActive);
+        Ok(())
+    }
+
+    fn note_enclave_activity() {
+        <LastEnclaveActivity<T>>::put(<system::Module<T>>::block_number());
+    }
+
+    /// Checks that `nonce` directly follows the last nonce used by `enclave`
+    fn ensure_next_nonce(enclave: &T::AccountId, nonce: u64) -> Result<(), Error<T>> {
+        let last = Self::enclave_nonce(enclave);
+        ensure!(nonce > last, Error::<T>::EnclaveNonceReplayed);
+        ensure!(nonce == last.saturating_add(1), Error::<T>::EnclaveNonceOutOfOrder);
+        Ok(())
+    }
+
+    /// Records the approval of `enclave` for releasing `withdrawal_ids`
+    ///
+    /// Returns true once the payload has been approved by `ReleaseThreshold` distinct enclaves.
+    fn approve_release(
+        enclave: &T::AccountId,
+        withdrawal_ids: &[WithdrawalId],
+    ) -> Result<bool, DispatchError> {
+        Self::approve_payload(enclave, T::Hashing::hash_of(&withdrawal_ids))
+    }
+
+    /// Records the approval of `enclave` for `payload`
+    ///
+    /// Returns true once the payload has been approved by `ReleaseThreshold` distinct enclaves.
+    fn approve_payload(enclave: &T::AccountId, payload: T::Hash) -> Result<bool, DispatchError> {
+        let threshold = Self::release_threshold().max(1);
+        let now = <system::Module<T>>::block_number();
+        let mut approval = match Self::release_approval(payload) {
+            Some(approval) if approval.expires_at > now => approval,
+            _ => ReleaseApproval {
+                approvals: Vec::new(),
+                expires_at: now.saturating_add(T::ApprovalTimeout::get()),
+            },
+        };
+        ensure!(
+            !approval.approvals.contains(enclave),
+            Error::<T>::ReleaseAlreadyApproved
+        );
+        approval.approvals.push(enclave.clone());
+        let approvals = approval.approvals.len() as u32;
+        Self::deposit_event(RawEvent::ReleaseApproved(
+            payload,
+            enclave.clone(),
+            approvals,
+            threshold,
+        ));
+        if approvals >= threshold {
+            <ReleaseApprovals<T>>::remove(payload);
+            return Ok(true);
+        }
+        if approvals == 1 {
+            <ApprovalExpiries<T>>::append(approval.expires_at, payload);
+        }
+        <ReleaseApprovals<T>>::insert(payload, approval);
+        Ok(false)
+    }
+
+    /// Drops release payloads which did not collect enough approvals in time
+    fn expire_release_approvals(now: T::BlockNumber) -> Weight {
+        let expiring = <ApprovalExpiries<T>>::take(now);
+        for payload in expiring.iter() {
+            if <ReleaseApprovals<T>>::contains_key(payload) {
+                <ReleaseApprovals<T>>::remove(payload);
+                Self::deposit_event(RawEvent::ReleaseApprovalExpired(*payload));
+            }
+        }
+        T::DbWeight::get().reads_writes(
+            1 + expiring.len() as Weight,
+            1 + expiring.len() as Weight,
+        )
+    }
+
+    /// Pays out a pending withdrawal request, or queues it if it exceeds the outflow limit
+    ///
+    /// While a challenge window is set the payout is delayed until the window passed.
+    fn settle_withdrawal(
+        withdrawal_id: WithdrawalId,
+        enclave: &T::AccountId,
+        mr_enclave: MrEnclave,
+    ) -> DispatchResult {
+        let request = Self::pending_withdrawal(withdrawal_id)?;
+        Self::ensure_not_paused(OcexOperation::Release, request.asset_id)?;
+        let now = <system::Module<T>>::block_number();
+        match Self::outflow_after(request.asset_id, request.amount, now) {
+            Some(buckets) => {
+                match Self::challenge_window() {
+                    Some(delay) => Self::delay_release(withdrawal_id, enclave, mr_enclave, now.saturating_add(delay)),
+                    None => Self::pay_out(withdrawal_id, request.clone(), enclave.clone(), mr_enclave)?,
+                }
+                <OutflowBuckets<T>>::insert(request.asset_id, buckets);
+            }
+            None => {
+                let unlock_at = now.saturating_add(T::QueuedReleaseDelay::get());
+                let queued = QueuedRelease {
+                    enclave: enclave.clone(),
+                    mr_enclave,
+                    unlock_at,
+                };
+                <QueuedReleases<T>>::insert(withdrawal_id, queued);
+                Self::set_withdrawal_status(withdrawal_id, WithdrawalStatus::Queued);
+                Self::deposit_event(RawEvent::ReleaseQueued(withdrawal_id, unlock_at));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pauses the releases of assets whose custody reserves fell below the reported liabilities
+    ///
+    /// Releases stay paused after the reserves recovered until governance resumes them.
+    fn check_reserves() -> Weight {
+        let liabilities = Self::liabilities();
+        let previous = Self::under_reserved_assets();
+        let custody = Self::get_account();
+        let mut under_reserved = Vec::new();
+        let mut writes: Weight = 0;
+        for (asset_id, owed) in liabilities.iter() {
+            let reserves = <T as Config>::Currency::free_balance(*asset_id, &custody);
+            if reserves >= *owed {
+                continue;
+            }
+            under_reserved.push(*asset_id);
+            if !previous.contains(asset_id) {
+                AssetPause::insert(OcexOperation::Release, *asset_id, true);
+                writes += 1;
+                Self::deposit_event(RawEvent::ReservesBelowLiabilities(*asset_id, reserves, *owed));
+                Self::deposit_event(RawEvent::OperationPaused(OcexOperation::Release, Some(*asset_id)));
+            }
+        }
+        for asset_id in previous.iter().filter(|asset_id| !under_reserved.contains(asset_id)) {
+            Self::deposit_event(RawEvent::ReservesRestored(*asset_id));
+        }
+        if under_reserved != previous {
+            UnderReservedAssets::put(under_reserved);
+            writes += 1;
+        }
+        T::DbWeight::get().reads_writes(2 + liabilities.len() as Weight, writes)
+    }
+
+    /// Records a release to be paid out in `execute_at` unless it is challenged before
+    fn delay_release(
+        withdrawal_id: WithdrawalId,
+        enclave: &T::AccountId,
+        mr_enclave: MrEnclave,
+        execute_at: T::BlockNumber,
+    ) {
+        let delayed = DelayedRelease {
+            enclave: enclave.clone(),
+            mr_enclave,
+            execute_at,
+            challenge: None,
+        };
+        <DelayedReleases<T>>::insert(withdrawal_id, delayed);
+        <DelayedReleaseSchedule<T>>::append(execute_at, withdrawal_id);
+        Self::set_withdrawal_status(withdrawal_id, WithdrawalStatus::Delayed(execute_at));
+        Self::deposit_event(RawEvent::ReleaseDelayed(withdrawal_id, execute_at));
+    }
+
+    /// Pays out the unchallenged delayed releases scheduled for `now`
+    ///
+    /// At most `MaxDelayedReleasesPerBlock` are paid out, the rest move to the next block.
+    fn execute_delayed_releases(now: T::BlockNumber) -> Weight {
+        let mut due = <DelayedReleaseSchedule<T>>::take(now);
+        if due.is_empty() {
+            return T::DbWeight::get().reads_writes(1, 1);
+        }
+        let max = T::MaxDelayedReleasesPerBlock::get() as usize;
+        if due.len() > max {
+            let deferred = due.split_off(max);
+            <DelayedReleaseSchedule<T>>::mutate(now.saturating_add(One::one()), |next| {
+                next.extend(deferred)
+            });
+        }
+        let count = due.len() as Weight;
+        for withdrawal_id in due {
+            // Challenged releases wait for governance, cancelled ones are gone
+            let unchallenged = Self::delayed_release(withdrawal_id)
+                .map_or(false, |delayed| delayed.challenge.is_none());
+            if unchallenged {
+                if let Err(error) = Self::pay_out_delayed_release(withdrawal_id) {
+                    Self::deposit_event(RawEvent::DelayedReleaseFailed(withdrawal_id, error));
+                }
+            }
+        }
+        T::DbWeight::get()
+            .reads_writes(2, 2)
+            .saturating_add(<T as Config>::WeightInfo::execute_delayed_release().saturating_mul(count))
+    }
+
+    /// Pays out a delayed release whose challenge window passed without a challenge
+    fn pay_out_delayed_release(withdrawal_id: WithdrawalId) -> DispatchResult {
+        let delayed = Self::delayed_release(withdrawal_id).ok_or(Error::<T>::ReleaseNotDelayed)?;
+        ensure!(delayed.challenge.is_none(), Error::<T>::ReleaseAlreadyChallenged);
+        ensure!(
+            <system::Module<T>>::block_number() >= delayed.execute_at,
+            Error::<T>::ChallengeWindowOpen
+        );
+        Self::ensure_not_exodus()?;
+        let request = Self::withdrawals(withdrawal_id).ok_or(Error::<T>::WithdrawalNotFound)?;
+        Self::ensure_not_paused(OcexOperation::Release, request.asset_id)?;
+        Self::pay_out(withdrawal_id, request, delayed.enclave, delayed.mr_enclave)?;
+        <DelayedReleases<T>>::remove(withdrawal_id);
+        Ok(())
+    }
+
+    fn set_withdrawal_status(withdrawal_id: WithdrawalId, status: WithdrawalStatus<T::BlockNumber>) {
+        <Withdrawals<T>>::mutate(withdrawal_id, |request| {
+            if let Some(request) = request {
+                request.status = status;
+            }
+        });
+    }
+
+    /// Returns the outflow buckets including `amount`, or `None` if the releases of the last
+    /// period would exceed the limit
+    ///
+    /// The period is split into `OutflowBucketsPerPeriod` buckets and a bucket is dropped once
+    /// it ended a full period ago, so the window rolls forward one bucket at a time.
+    fn outflow_after(
+        asset_id: AssetId,
+        amount: T::Balance,
+        now: T::BlockNumber,
+    ) -> Option<Vec<OutflowBucket<T::Balance, T::BlockNumber>>> {
+        let limit = match Self::outflow_limit(asset_id) {
+            Some(limit) => limit,
+            None => return Some(Vec::new()),
+        };
+        let buckets_per_period = T::BlockNumber::from(T::OutflowBucketsPerPeriod::get().max(1));
+        let bucket_len = (limit.period / buckets_per_period).max(One::one());
+        let bucket_start = now.saturating_sub(now % bucket_len);
+        let mut buckets = Self::outflow_buckets(asset_id);
+        buckets.retain(|bucket| {
+            bucket.start.saturating_add(bucket_len).saturating_add(limit.period) > now
+        });
+        match buckets.last_mut() {
+            Some(bucket) if bucket.start == bucket_start => {
+                bucket.released = bucket.released.saturating_add(amount)
+            }
+            _ => buckets.push(OutflowBucket {
+                start: bucket_start,
+                released: amount,
+            }),
+        }
+        let released = buckets
+            .iter()
+            .fold(T::Balance::zero(), |total, bucket| total.saturating_add(bucket.released));
+        if released > limit.max_amount {
+            return None;
+        }
+        Some(buckets)
+    }
+
+    /// Transfers the requested amount out of custody and closes the request
+    fn pay_out(
+        withdrawal_id: WithdrawalId,
+        request: WithdrawalRequestOf<T>,
+        enclave: T::AccountId,
+        mr_enclave: MrEnclave,
+    ) -> DispatchResult {
+        <T as Config>::Currency::transfer(
+            request.asset_id,
+            &Self::get_account(),
+            &request.destination,
+            request.amount,
+        )?;
+        Self::close_withdrawal(
+            withdrawal_id,
+            &request,
+            WithdrawalStatus::Settled(<system::Module<T>>::block_number()),
+        );
+        Self::reduce_account_deposits(&request.account, request.asset_id, request.amount);
+        Self::note_snapshot_flow(&request.account, request.asset_id, Zero::zero(), request.amount);
+        Self::deposit_event(RawEvent::WithdrawalSettled(
+            withdrawal_id,
+            request.asset_id,
+            request.destination,
+            request.amount,
+            enclave,
+            mr_enclave,
+        ));
+        Ok(())
+    }
+
+    /// Returns the withdrawal request if it is still waiting for the enclave
+    fn pending_withdrawal(withdrawal_id: WithdrawalId) -> Result<WithdrawalRequestOf<T>, Error<T>> {
+        let request = Self::withdrawals(withdrawal_id).ok_or(Error::<T>::WithdrawalNotFound)?;
+        ensure!(
+            request.status == WithdrawalStatus::Pending,
+            Error::<T>::WithdrawalNotPending
+        );
+        Ok(request)
+    }
+
+    /// Stores the final status of a withdrawal request and schedules its pruning
+    fn close_withdrawal(
+        withdrawal_id: WithdrawalId,
+        request: &WithdrawalRequestOf<T>,
+        status: WithdrawalStatus<T::BlockNumber>,
+    ) {
+        let mut request = request.clone();
+        <PendingWithdrawals<T>>::remove(&request.account, withdrawal_id);
+        <PendingWithdrawalAmounts<T>>::mutate_exists(&request.account, request.asset_id, |pending| {
+            *pending = pending
+                .map(|pending| pending.saturating_sub(request.amount))
+                .filter(|pending| !pending.is_zero());
+        });
+        let retention = T::WithdrawalRetention::get().max(One::one());
+        let prune_at = <system::Module<T>>::block_number().saturating_add(retention);
+        <WithdrawalPruneSchedule<T>>::append(prune_at, withdrawal_id);
+        request.status = status;
+        <Withdrawals<T>>::insert(withdrawal_id, request);
+    }
+
+    /// Removes the settled and rejected withdrawal requests whose retention ends at `now`
+    fn prune_withdrawals(now: T::BlockNumber) -> Weight {
+        let pruned = <WithdrawalPruneSchedule<T>>::take(now);
+        for withdrawal_id in pruned.iter() {
+            <Withdrawals<T>>::remove(withdrawal_id);
+        }
+        T::DbWeight::get().reads_writes(1, 1 + pruned.len() as Weight)
+    }
+}
+
+impl<T: Config> MarketRegistry<T::Balance> for Module<T> {
+    fn market_info(market: &TradingPair) -> Option<MarketInfo<T::Balance>> {
+        Self::market(*market)
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_market(market: TradingPair, info: MarketInfo<T::Balance>) {
+        <Markets<T>>::insert(market, info);
+    }
 }
 
 // TODO: Set genesis storage to have some balance for PDEX and DOT for alice and bob
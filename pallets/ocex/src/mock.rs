@@ -0,0 +1,293 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+
+use crate as polkadex_ocex;
+use frame_support::{assert_ok, ord_parameter_types, parameter_types};
+use frame_system::EnsureSignedBy;
+use orml_currencies::BasicCurrencyAdapter;
+use orml_traits::parameter_type_with_key;
+use polkadex_primitives::assets::AssetId;
+use sp_core::{sr25519, H256};
+use std::cell::RefCell;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Storage, Event<T>},
+        PalletBalances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        OrmlToken: orml_tokens::{Pallet, Call, Storage, Event<T>},
+        Currencies: orml_currencies::{Pallet, Call, Event<T>},
+        Fungible: polkadex_fungible_assets::{Pallet, Call, Event<T>},
+        Ocex: polkadex_ocex::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+pub type Balance = u128;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type DbWeight = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u128 = 500;
+    pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = MaxLocks;
+    type Balance = Balance;
+    type Event = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = frame_system::Pallet<Test>;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub TreasuryModuleAccount: u64 = 100;
+}
+
+parameter_type_with_key! {
+    pub ExistentialDeposits: |_currency_id: AssetId| -> Balance {
+        Zero::zero()
+    };
+}
+
+impl orml_tokens::Config for Test {
+    type Event = ();
+    type Balance = Balance;
+    type Amount = i128;
+    type CurrencyId = AssetId;
+    type WeightInfo = ();
+    type ExistentialDeposits = ExistentialDeposits;
+    type OnDust = orml_tokens::TransferDust<Test, TreasuryModuleAccount>;
+}
+
+parameter_types! {
+    pub const GetNativeCurrencyId: AssetId = AssetId::POLKADEX;
+}
+
+pub type AdaptedBasicCurrency = BasicCurrencyAdapter<Test, PalletBalances, i128, u128>;
+
+impl orml_currencies::Config for Test {
+    type Event = ();
+    type MultiCurrency = OrmlToken;
+    type NativeCurrency = AdaptedBasicCurrency;
+    type GetNativeCurrencyId = GetNativeCurrencyId;
+    type WeightInfo = ();
+}
+
+ord_parameter_types! {
+    pub const Governance: u64 = 6;
+}
+
+impl polkadex_fungible_assets::Config for Test {
+    type Event = ();
+    type TreasuryAccountId = TreasuryModuleAccount;
+    type GovernanceOrigin = EnsureSignedBy<Governance, u64>;
+    type NativeCurrency = AdaptedBasicCurrency;
+    type WeightInfo = ();
+}
+
+thread_local! {
+    static STATIC_KEYS: RefCell<bool> = RefCell::new(true);
+}
+
+/// Makes the enclave authority use or ignore the enclave keys set by governance
+pub fn set_static_keys(accepted: bool) {
+    STATIC_KEYS.with(|static_keys| *static_keys.borrow_mut() = accepted);
+}
+
+/// Static enclaves which can be switched to ignore static keys like the registry does
+pub struct TestEnclaves;
+
+impl EnclaveAuthority<u64> for TestEnclaves {
+    fn mr_enclave(enclave: &u64) -> Option<MrEnclave> {
+        StaticEnclaves::<Test>::mr_enclave(enclave)
+    }
+
+    fn enclaves() -> Vec<(u64, MrEnclave)> {
+        StaticEnclaves::<Test>::enclaves()
+    }
+
+    fn accepts_static_keys() -> bool {
+        STATIC_KEYS.with(|static_keys| *static_keys.borrow())
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_enclave(enclave: &u64, mr_enclave: MrEnclave) {
+        StaticEnclaves::<Test>::register_enclave(enclave, mr_enclave)
+    }
+}
+
+/// Trading key whose account is the wrapped number
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct TestTradingKey(pub u64);
+
+impl IdentifyAccount for TestTradingKey {
+    type AccountId = u64;
+
+    fn into_account(self) -> u64 {
+        self.0
+    }
+}
+
+/// Key of the account read from the first eight bytes, used by the benchmarks
+impl From<sr25519::Public> for TestTradingKey {
+    fn from(key: sr25519::Public) -> Self {
+        let mut account = [0u8; 8];
+        account.copy_from_slice(&key.0[..8]);
+        TestTradingKey(u64::from_le_bytes(account))
+    }
+}
+
+parameter_types! {
+    pub const OcexId: PalletId = PalletId(*b"polka/ex");
+    pub const MaxReleaseBatch: u32 = 10;
+    pub const WithdrawalRetention: u64 = 10;
+    pub const ExodusTimeout: u64 = 100;
+    pub const HeartbeatInterval: u64 = 10;
+    pub const QueuedReleaseDelay: u64 = 20;
+    pub const OutflowBucketsPerPeriod: u32 = 5;
+    pub const ApprovalTimeout: u64 = 5;
+    pub const HandoverTimeout: u64 = 10;
+    pub const MaxFillsPerBatch: u32 = 10;
+    pub const MaxTradingKeys: u32 = 2;
+    pub const MinTraderStake: Balance = 1_000;
+    pub const StakePerFeelessTransaction: Balance = 500;
+    pub const FeelessWindow: u64 = 10;
+    pub const MaxFeelessPerBlock: u32 = 3;
+    pub const TraderUnbondingPeriod: u64 = 20;
+    pub const ChallengeBond: Balance = 100;
+    pub const MaxDelayedReleasesPerBlock: u32 = 1;
+    pub const MaxLiabilityAssets: u32 = 2;
+}
+
+impl Config for Test {
+    type Event = ();
+    type OcexId = OcexId;
+    type Currency = OrmlToken;
+    type GovernanceOrigin = EnsureSignedBy<Governance, u64>;
+    type EnclaveAuthority = TestEnclaves;
+    type MaxReleaseBatch = MaxReleaseBatch;
+    type WithdrawalRetention = WithdrawalRetention;
+    type ExodusTimeout = ExodusTimeout;
+    type HeartbeatInterval = HeartbeatInterval;
+    type QueuedReleaseDelay = QueuedReleaseDelay;
+    type OutflowBucketsPerPeriod = OutflowBucketsPerPeriod;
+    type ApprovalTimeout = ApprovalTimeout;
+    type HandoverTimeout = HandoverTimeout;
+    type MaxFillsPerBatch = MaxFillsPerBatch;
+    type TradingKey = TestTradingKey;
+    type MaxTradingKeys = MaxTradingKeys;
+    type FeelessCalls = ();
+    type MinTraderStake = MinTraderStake;
+    type StakePerFeelessTransaction = StakePerFeelessTransaction;
+    type FeelessWindow = FeelessWindow;
+    type MaxFeelessPerBlock = MaxFeelessPerBlock;
+    type TraderUnbondingPeriod = TraderUnbondingPeriod;
+    type ChallengeBond = ChallengeBond;
+    type MaxDelayedReleasesPerBlock = MaxDelayedReleasesPerBlock;
+    type MaxLiabilityAssets = MaxLiabilityAssets;
+    type WeightInfo = ();
+}
+
+pub type PolkadexOcex = Pallet<Test>;
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+pub const ENCLAVE: u64 = 10;
+pub const GOVERNANCE: u64 = 6;
+pub const MR_ENCLAVE: MrEnclave = [7u8; 32];
+
+pub fn new_tester() -> sp_io::TestExternalities {
+    let storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        for account in [ALICE, BOB].iter() {
+            for asset_id in [AssetId::POLKADEX, AssetId::DOT].iter() {
+                assert_ok!(OrmlToken::deposit(*asset_id, account, 10_000));
+            }
+        }
+    });
+    ext
+}
+
+/// Adds `ENCLAVE` as a static enclave running an allowed build and makes it live
+pub fn new_tester_with_enclave() -> sp_io::TestExternalities {
+    let mut ext = new_tester();
+    ext.execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        assert_ok!(PolkadexOcex::add_static_enclave(governance.clone(), ENCLAVE, MR_ENCLAVE));
+        assert_ok!(PolkadexOcex::allow_mrenclave(governance.clone(), MR_ENCLAVE, None));
+        assert_ok!(PolkadexOcex::set_asset_config(
+            governance,
+            AssetId::DOT,
+            AssetDepositConfig {
+                enabled: true,
+                min_deposit: 1,
+                max_deposit: None,
+                max_per_account: None,
+            }
+        ));
+        assert_ok!(PolkadexOcex::heartbeat(Origin::signed(ENCLAVE)));
+    });
+    ext
+}
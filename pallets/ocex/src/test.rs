@@ -0,0 +1,1239 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::mock::{self, *};
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+use sp_core::H256;
+use sp_runtime::{traits::BlakeTwo256, DispatchError};
+
+use super::*;
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
+use polkadex_primitives::assets::AssetId;
+
+fn deposit_and_withdraw(to: u64, amount: Balance) -> WithdrawalId {
+    assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+    let withdrawal_id = PolkadexOcex::next_withdrawal_id();
+    assert_ok!(PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, to, amount));
+    withdrawal_id
+}
+
+fn run_to_block(block: u64) {
+    System::set_block_number(block);
+    PolkadexOcex::on_initialize(block);
+}
+
+/// Registers `key` as a trading key of `main`, confirmed by the key
+fn register_trading_key(main: u64, key: u64) {
+    assert_ok!(PolkadexOcex::register_trading_key(Origin::signed(main), TestTradingKey(key)));
+    assert_ok!(PolkadexOcex::confirm_trading_key(Origin::signed(key), main));
+}
+
+/// Releases a withdrawal of 400 DOT to Alice into a challenge window of five blocks
+fn delayed_release() -> WithdrawalId {
+    assert_ok!(PolkadexOcex::set_challenge_window(Origin::signed(GOVERNANCE), Some(5)));
+    let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+    assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+    withdrawal_id
+}
+
+fn withdrawal_status(withdrawal_id: WithdrawalId) -> WithdrawalStatus<u64> {
+    PolkadexOcex::withdrawals(withdrawal_id).unwrap().status
+}
+
+#[test]
+fn test_static_enclave_releases_withdrawal() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &PolkadexOcex::get_account()), 1_000);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_000);
+
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &PolkadexOcex::get_account()), 600);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_400);
+        assert_eq!(
+            PolkadexOcex::withdrawals(withdrawal_id).unwrap().status,
+            WithdrawalStatus::Settled(1)
+        );
+    });
+}
+
+#[test]
+fn test_unknown_enclave_can_not_release() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(BOB), withdrawal_id, 1),
+            Error::<Test>::NotARegisteredEnclave
+        );
+    });
+}
+
+#[test]
+fn test_removed_static_enclave_can_not_release() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::remove_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE));
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1),
+            Error::<Test>::NotARegisteredEnclave
+        );
+    });
+}
+
+#[test]
+fn test_static_enclaves_rejected_when_authority_ignores_them() {
+    new_tester().execute_with(|| {
+        mock::set_static_keys(false);
+        assert_noop!(
+            PolkadexOcex::add_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE, MR_ENCLAVE),
+            Error::<Test>::StaticEnclavesNotSupported
+        );
+        assert_noop!(
+            PolkadexOcex::remove_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE),
+            Error::<Test>::StaticEnclavesNotSupported
+        );
+        mock::set_static_keys(true);
+        assert_ok!(PolkadexOcex::add_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE, MR_ENCLAVE));
+    });
+}
+
+#[test]
+fn test_static_enclave_needs_allowed_build() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_ok!(PolkadexOcex::add_static_enclave(Origin::signed(GOVERNANCE), 11, [8u8; 32]));
+        assert_noop!(
+            PolkadexOcex::heartbeat(Origin::signed(11)),
+            Error::<Test>::MrEnclaveNotAllowed
+        );
+    });
+}
+
+#[test]
+fn test_static_enclaves_are_managed_by_governance() {
+    new_tester().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::add_static_enclave(Origin::signed(ALICE), ENCLAVE, MR_ENCLAVE),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(PolkadexOcex::add_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE, MR_ENCLAVE));
+        assert_noop!(
+            PolkadexOcex::add_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE, [8u8; 32]),
+            Error::<Test>::StaticEnclaveExists
+        );
+        assert_eq!(PolkadexOcex::static_enclave(ENCLAVE), Some(MR_ENCLAVE));
+        assert_ok!(PolkadexOcex::remove_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE));
+        assert_noop!(
+            PolkadexOcex::remove_static_enclave(Origin::signed(GOVERNANCE), ENCLAVE),
+            Error::<Test>::StaticEnclaveNotFound
+        );
+    });
+}
+
+#[test]
+fn test_registered_enclaves_lists_static_enclaves() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_eq!(
+            PolkadexOcex::registered_enclaves(),
+            vec![EnclaveStatus {
+                account: ENCLAVE,
+                mr_enclave: MR_ENCLAVE,
+                allowed: true,
+                live: true,
+            }]
+        );
+    });
+}
+
+#[test]
+fn test_custody_balances_include_native_asset() {
+    new_tester().execute_with(|| {
+        let custody = PolkadexOcex::get_account();
+        assert!(PolkadexOcex::custody_balances().is_empty());
+        assert_ok!(OrmlToken::deposit(AssetId::DOT, &custody, 300));
+        assert_ok!(<mock::Test as Config>::Currency::deposit(AssetId::POLKADEX, &custody, 500));
+        let balances = PolkadexOcex::custody_balances();
+        assert_eq!(balances.len(), 2);
+        assert!(balances.contains(&(AssetId::DOT, 300)));
+        assert!(balances.contains(&(AssetId::POLKADEX, 500)));
+    });
+}
+
+#[test]
+fn test_deposit_requires_live_enclave() {
+    new_tester().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000),
+            Error::<Test>::NoLiveEnclave
+        );
+    });
+}
+
+#[test]
+fn test_trading_key_must_confirm_registration() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOcex::register_trading_key(Origin::signed(ALICE), TestTradingKey(20)));
+        assert_eq!(PolkadexOcex::trading_key_owner(&20), None);
+        // Only the proposed key can confirm, and only for the account which proposed it
+        assert_noop!(
+            PolkadexOcex::confirm_trading_key(Origin::signed(BOB), ALICE),
+            Error::<Test>::TradingKeyNotProposed
+        );
+        assert_noop!(
+            PolkadexOcex::confirm_trading_key(Origin::signed(20), BOB),
+            Error::<Test>::TradingKeyNotProposed
+        );
+        assert_ok!(PolkadexOcex::confirm_trading_key(Origin::signed(20), ALICE));
+        assert_eq!(PolkadexOcex::trading_key_owner(&20), Some(ALICE));
+        assert_eq!(PolkadexOcex::pending_trading_key(&ALICE), None);
+    });
+}
+
+#[test]
+fn test_withdrawal_to_trading_key_is_rejected() {
+    new_tester_with_enclave().execute_with(|| {
+        register_trading_key(ALICE, 20);
+        assert_noop!(
+            PolkadexOcex::withdraw(Origin::signed(20), AssetId::DOT, 20, 100),
+            Error::<Test>::TradingKeyCannotWithdraw
+        );
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_noop!(
+            PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, 20, 400),
+            Error::<Test>::TradingKeyDestination
+        );
+    });
+}
+
+#[test]
+fn test_feeless_quota_follows_stake() {
+    new_tester().execute_with(|| {
+        let call = mock::Call::System(frame_system::Call::remark(vec![]));
+        assert_noop!(
+            PolkadexOcex::register_trader(Origin::signed(ALICE), 999),
+            Error::<Test>::TraderStakeTooLow
+        );
+        assert_ok!(PolkadexOcex::register_trader(Origin::signed(ALICE), 1_000));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 1_000);
+        register_trading_key(ALICE, 20);
+        assert_eq!(PolkadexOcex::feeless_trader(&BOB, &call), None);
+
+        // 1_000 staked covers two feeless transactions per window
+        for _ in 0..2 {
+            assert_eq!(PolkadexOcex::feeless_trader(&20, &call), Some(ALICE));
+            PolkadexOcex::note_feeless_transaction(&ALICE);
+        }
+        assert_eq!(PolkadexOcex::feeless_trader(&ALICE, &call), None);
+
+        System::set_block_number(11);
+        assert_eq!(PolkadexOcex::feeless_trader(&ALICE, &call), Some(ALICE));
+
+        assert_ok!(PolkadexOcex::unregister_trader(Origin::signed(ALICE)));
+        assert_eq!(PolkadexOcex::feeless_trader(&ALICE, &call), None);
+    });
+}
+
+#[test]
+fn test_trader_stake_unbonds_before_release() {
+    new_tester().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::withdraw_unbonded_stake(Origin::signed(ALICE)),
+            Error::<Test>::NoUnbondingStake
+        );
+        assert_ok!(PolkadexOcex::register_trader(Origin::signed(ALICE), 1_000));
+        assert_ok!(PolkadexOcex::unregister_trader(Origin::signed(ALICE)));
+        assert_eq!(PolkadexOcex::trader_stake(&ALICE), None);
+        assert_eq!(PolkadexOcex::unbonding_trader_stake(&ALICE), Some((1_000, 21)));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 1_000);
+
+        // Registering again stakes fresh funds and restarts the unbonding of both stakes
+        System::set_block_number(11);
+        assert_ok!(PolkadexOcex::register_trader(Origin::signed(ALICE), 1_500));
+        assert_ok!(PolkadexOcex::unregister_trader(Origin::signed(ALICE)));
+        assert_eq!(PolkadexOcex::unbonding_trader_stake(&ALICE), Some((2_500, 31)));
+
+        System::set_block_number(30);
+        assert_noop!(
+            PolkadexOcex::withdraw_unbonded_stake(Origin::signed(ALICE)),
+            Error::<Test>::StakeStillUnbonding
+        );
+        System::set_block_number(31);
+        assert_ok!(PolkadexOcex::withdraw_unbonded_stake(Origin::signed(ALICE)));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 0);
+        assert_eq!(PolkadexOcex::unbonding_trader_stake(&ALICE), None);
+    });
+}
+
+#[test]
+fn test_feeless_tag_changes_with_included_transactions() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOcex::register_trader(Origin::signed(ALICE), 1_000));
+        register_trading_key(ALICE, 20);
+        let tag = PolkadexOcex::feeless_tag(&ALICE);
+        // Trading keys send under the tag of their main account
+        assert_eq!(PolkadexOcex::feeless_tag(&PolkadexOcex::main_account(&20)), tag);
+        PolkadexOcex::note_feeless_transaction(&ALICE);
+        let next = PolkadexOcex::feeless_tag(&ALICE);
+        assert_ne!(next, tag);
+        assert_ne!(PolkadexOcex::feeless_tag(&BOB), next);
+    });
+}
+
+#[test]
+fn test_delayed_release_is_paid_after_challenge_window() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::set_challenge_window(Origin::signed(GOVERNANCE), Some(0)),
+            Error::<Test>::InvalidChallengeWindow
+        );
+        let withdrawal_id = delayed_release();
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Delayed(6));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_000);
+
+        run_to_block(5);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_000);
+        run_to_block(6);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_400);
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Settled(6));
+        assert!(PolkadexOcex::delayed_release(withdrawal_id).is_none());
+    });
+}
+
+#[test]
+fn test_upheld_challenge_cancels_release() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = delayed_release();
+        assert_ok!(PolkadexOcex::challenge_release(Origin::signed(BOB), withdrawal_id));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &BOB), 100);
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Challenged);
+        assert_noop!(
+            PolkadexOcex::challenge_release(Origin::signed(ALICE), withdrawal_id),
+            Error::<Test>::ReleaseAlreadyChallenged
+        );
+
+        run_to_block(6);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_000);
+
+        assert_ok!(PolkadexOcex::cancel_delayed_release(Origin::signed(GOVERNANCE), withdrawal_id));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &BOB), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_000);
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Rejected(6));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &PolkadexOcex::get_account()), 1_000);
+    });
+}
+
+#[test]
+fn test_frivolous_challenge_is_slashed() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = delayed_release();
+        assert_ok!(PolkadexOcex::challenge_release(Origin::signed(BOB), withdrawal_id));
+        run_to_block(6);
+
+        assert_ok!(PolkadexOcex::dismiss_challenge(Origin::signed(GOVERNANCE), withdrawal_id));
+        assert_eq!(OrmlToken::total_balance(AssetId::POLKADEX, &BOB), 9_900);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &TreasuryModuleAccount::get()), 100);
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Delayed(7));
+
+        run_to_block(7);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_400);
+    });
+}
+
+#[test]
+fn test_failed_delayed_release_can_be_executed_later() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = delayed_release();
+        let governance = Origin::signed(GOVERNANCE);
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Release, Some(AssetId::DOT), true));
+        run_to_block(6);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_000);
+        assert_noop!(
+            PolkadexOcex::challenge_release(Origin::signed(BOB), withdrawal_id),
+            Error::<Test>::ChallengeWindowClosed
+        );
+
+        assert_ok!(PolkadexOcex::set_paused(governance, OcexOperation::Release, Some(AssetId::DOT), false));
+        assert_ok!(PolkadexOcex::execute_delayed_release(Origin::signed(BOB), withdrawal_id));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_400);
+    });
+}
+
+#[test]
+fn test_reserve_shortfall_pauses_releases() {
+    new_tester_with_enclave().execute_with(|| {
+        let enclave = Origin::signed(ENCLAVE);
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_ok!(PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 1_000)], 1));
+        run_to_block(2);
+        assert!(PolkadexOcex::under_reserved_assets().is_empty());
+        assert_eq!(
+            PolkadexOcex::reserve_ratios(),
+            vec![(
+                AssetId::DOT,
+                ReserveStatus { reserves: 1_000, liabilities: 1_000, ratio: Some(FixedU128::one()) }
+            )]
+        );
+
+        assert_ok!(PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 1_500)], 2));
+        run_to_block(3);
+        assert_eq!(PolkadexOcex::under_reserved_assets(), vec![AssetId::DOT]);
+        assert!(PolkadexOcex::asset_paused(OcexOperation::Release, AssetId::DOT));
+
+        assert_ok!(PolkadexOcex::report_liabilities(enclave, vec![(AssetId::DOT, 800)], 3));
+        run_to_block(4);
+        assert!(PolkadexOcex::under_reserved_assets().is_empty());
+        assert!(PolkadexOcex::asset_paused(OcexOperation::Release, AssetId::DOT));
+    });
+}
+
+#[test]
+fn test_liabilities_report_is_checked() {
+    new_tester_with_enclave().execute_with(|| {
+        let enclave = Origin::signed(ENCLAVE);
+        assert_noop!(
+            PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 1), (AssetId::DOT, 2)], 1),
+            Error::<Test>::DuplicateLiabilityAsset
+        );
+        assert_noop!(
+            PolkadexOcex::report_liabilities(
+                enclave.clone(),
+                vec![(AssetId::DOT, 1), (AssetId::POLKADEX, 2), (AssetId::CHAINSAFE(sp_core::H160::zero()), 3)],
+                1
+            ),
+            Error::<Test>::TooManyLiabilityAssets
+        );
+        assert_noop!(
+            PolkadexOcex::report_liabilities(Origin::signed(BOB), vec![(AssetId::DOT, 1)], 1),
+            Error::<Test>::NotARegisteredEnclave
+        );
+        assert_noop!(
+            PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 1)], 2),
+            Error::<Test>::LiabilitiesNonceMismatch
+        );
+        assert_ok!(PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 1)], 1));
+        assert_eq!(PolkadexOcex::liabilities_nonce(), 1);
+        // An applied report can not be replayed
+        assert_noop!(
+            PolkadexOcex::report_liabilities(enclave, vec![(AssetId::DOT, 2)], 1),
+            Error::<Test>::LiabilitiesNonceMismatch
+        );
+    });
+}
+
+#[test]
+fn test_expired_mrenclave_can_not_release() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::expire_mrenclave(Origin::signed(GOVERNANCE), MR_ENCLAVE, 5));
+        System::set_block_number(5);
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1),
+            Error::<Test>::MrEnclaveExpired
+        );
+    });
+}
+
+#[test]
+fn test_removed_mrenclave_can_not_release() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::remove_mrenclave(Origin::signed(GOVERNANCE), MR_ENCLAVE));
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1),
+            Error::<Test>::MrEnclaveNotAllowed
+        );
+    });
+}
+
+#[test]
+fn test_mrenclave_allowlist_is_managed_by_governance() {
+    new_tester().execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        assert_noop!(
+            PolkadexOcex::allow_mrenclave(Origin::signed(ALICE), MR_ENCLAVE, None),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            PolkadexOcex::expire_mrenclave(governance.clone(), MR_ENCLAVE, 5),
+            Error::<Test>::MrEnclaveNotAllowed
+        );
+        assert_ok!(PolkadexOcex::allow_mrenclave(governance.clone(), MR_ENCLAVE, Some(10)));
+        assert_eq!(
+            PolkadexOcex::allowed_mrenclave(MR_ENCLAVE),
+            Some(MrEnclaveInfo { allowed_at: 1, expires_at: Some(10) })
+        );
+        assert_noop!(
+            PolkadexOcex::allow_mrenclave(governance.clone(), MR_ENCLAVE, None),
+            Error::<Test>::MrEnclaveAlreadyAllowed
+        );
+        assert_ok!(PolkadexOcex::remove_mrenclave(governance.clone(), MR_ENCLAVE));
+        assert_noop!(
+            PolkadexOcex::remove_mrenclave(governance, MR_ENCLAVE),
+            Error::<Test>::MrEnclaveNotAllowed
+        );
+    });
+}
+
+#[test]
+fn test_withdraw_is_not_bounded_by_deposits() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, ALICE, 0),
+            Error::<Test>::WithdrawalAmountZero
+        );
+        // Balances bought through trades were never deposited by the account itself
+        assert_ok!(PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, ALICE, 600));
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_ok!(PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, ALICE, 1_001));
+        assert_eq!(PolkadexOcex::pending_withdrawal_amount(ALICE, AssetId::DOT), 1_601);
+    });
+}
+
+#[test]
+fn test_closed_withdrawals_are_pruned() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_eq!(PolkadexOcex::pending_withdrawal_amount(ALICE, AssetId::DOT), 0);
+        assert!(PolkadexOcex::withdrawals(withdrawal_id).is_some());
+        run_to_block(10);
+        assert!(PolkadexOcex::withdrawals(withdrawal_id).is_some());
+        run_to_block(11);
+        assert!(PolkadexOcex::withdrawals(withdrawal_id).is_none());
+        assert!(PolkadexOcex::withdrawals_pruned_at(11).is_empty());
+    });
+}
+
+#[test]
+fn test_enclave_nonce_can_not_be_replayed() {
+    new_tester_with_enclave().execute_with(|| {
+        let first = deposit_and_withdraw(ALICE, 400);
+        let second = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), first, 1));
+        assert_eq!(PolkadexOcex::enclave_nonce(ENCLAVE), 1);
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), second, 1),
+            Error::<Test>::EnclaveNonceReplayed
+        );
+        assert_noop!(
+            PolkadexOcex::reject_withdrawal(Origin::signed(ENCLAVE), second, 0),
+            Error::<Test>::EnclaveNonceReplayed
+        );
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), second, 3),
+            Error::<Test>::EnclaveNonceOutOfOrder
+        );
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), second, 2));
+        assert_eq!(PolkadexOcex::enclave_nonce(ENCLAVE), 2);
+    });
+}
+
+#[test]
+fn test_release_batch_pays_all_withdrawals() {
+    new_tester_with_enclave().execute_with(|| {
+        let first = deposit_and_withdraw(ALICE, 400);
+        let second = deposit_and_withdraw(BOB, 300);
+        assert_ok!(PolkadexOcex::release_batch(Origin::signed(ENCLAVE), vec![first, second], 1));
+        assert_eq!(withdrawal_status(first), WithdrawalStatus::Settled(1));
+        assert_eq!(withdrawal_status(second), WithdrawalStatus::Settled(1));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 8_400);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 10_300);
+    });
+}
+
+#[test]
+fn test_release_batch_rolls_back_as_a_whole() {
+    new_tester_with_enclave().execute_with(|| {
+        let first = deposit_and_withdraw(ALICE, 400);
+        let second = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::reject_withdrawal(Origin::signed(ENCLAVE), second, 1));
+        assert_noop!(
+            PolkadexOcex::release_batch(Origin::signed(ENCLAVE), vec![first, second], 2),
+            Error::<Test>::WithdrawalNotPending
+        );
+        assert_noop!(
+            PolkadexOcex::release_batch(Origin::signed(ENCLAVE), vec![first, 99], 2),
+            Error::<Test>::WithdrawalNotFound
+        );
+        assert_eq!(withdrawal_status(first), WithdrawalStatus::Pending);
+        assert_eq!(PolkadexOcex::enclave_nonce(ENCLAVE), 1);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 8_000);
+    });
+}
+
+#[test]
+fn test_release_batch_size_is_bounded() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::release_batch(Origin::signed(ENCLAVE), vec![], 1),
+            Error::<Test>::EmptyReleaseBatch
+        );
+        assert_noop!(
+            PolkadexOcex::release_batch(Origin::signed(ENCLAVE), (0..11).collect(), 1),
+            Error::<Test>::ReleaseBatchTooLarge
+        );
+    });
+}
+
+/// Commits a snapshot over the balances of Alice and Bob and returns the proof of Alice
+fn commit_two_balances(epoch: SnapshotEpoch, alice: Balance, bob: Balance) -> Vec<H256> {
+    let alice_leaf = PolkadexOcex::balance_leaf(&ALICE, AssetId::DOT, alice);
+    let bob_leaf = PolkadexOcex::balance_leaf(&BOB, AssetId::DOT, bob);
+    let root = if alice_leaf <= bob_leaf {
+        BlakeTwo256::hash_of(&(alice_leaf, bob_leaf))
+    } else {
+        BlakeTwo256::hash_of(&(bob_leaf, alice_leaf))
+    };
+    assert_ok!(PolkadexOcex::commit_balance_snapshot(Origin::signed(ENCLAVE), epoch, root));
+    vec![bob_leaf]
+}
+
+#[test]
+fn test_balance_proof_is_verified_against_snapshot() {
+    new_tester_with_enclave().execute_with(|| {
+        let proof = commit_two_balances(1, 700, 300);
+        assert!(PolkadexOcex::verify_balance_proof(1, &ALICE, AssetId::DOT, 700, &proof));
+        assert!(!PolkadexOcex::verify_balance_proof(1, &ALICE, AssetId::DOT, 701, &proof));
+        assert!(!PolkadexOcex::verify_balance_proof(1, &BOB, AssetId::DOT, 700, &proof));
+        assert!(!PolkadexOcex::verify_balance_proof(1, &ALICE, AssetId::DOT, 700, &[]));
+        assert!(!PolkadexOcex::verify_balance_proof(2, &ALICE, AssetId::DOT, 700, &proof));
+    });
+}
+
+#[test]
+fn test_snapshot_epochs_increase() {
+    new_tester_with_enclave().execute_with(|| {
+        commit_two_balances(2, 700, 300);
+        assert_noop!(
+            PolkadexOcex::commit_balance_snapshot(Origin::signed(ENCLAVE), 2, H256::zero()),
+            Error::<Test>::SnapshotEpochNotIncreasing
+        );
+        assert_noop!(
+            PolkadexOcex::commit_balance_snapshot(Origin::signed(BOB), 3, H256::zero()),
+            Error::<Test>::NotARegisteredEnclave
+        );
+        assert_eq!(PolkadexOcex::latest_snapshot_epoch(), Some(2));
+    });
+}
+
+#[test]
+fn test_exodus_starts_after_enclave_timeout() {
+    new_tester_with_enclave().execute_with(|| {
+        run_to_block(200);
+        assert_eq!(PolkadexOcex::exodus_mode(), None);
+        assert_ok!(PolkadexOcex::heartbeat(Origin::signed(ENCLAVE)));
+        commit_two_balances(1, 1_000, 0);
+        run_to_block(300);
+        assert_eq!(PolkadexOcex::exodus_mode(), None);
+        run_to_block(301);
+        assert_eq!(PolkadexOcex::exodus_mode(), Some(1));
+        assert_noop!(
+            PolkadexOcex::commit_balance_snapshot(Origin::signed(ENCLAVE), 2, H256::zero()),
+            Error::<Test>::ExodusModeActive
+        );
+        assert_ok!(PolkadexOcex::exit_exodus_mode(Origin::signed(GOVERNANCE), ENCLAVE));
+        assert_eq!(PolkadexOcex::exodus_mode(), None);
+    });
+}
+
+#[test]
+fn test_exodus_claim_accounts_for_flows_after_snapshot() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        let proof = commit_two_balances(1, 1_000, 0);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 500));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 8_900);
+        run_to_block(102);
+        assert_eq!(PolkadexOcex::exodus_mode(), Some(1));
+        assert_noop!(
+            PolkadexOcex::exodus_claim(Origin::signed(ALICE), AssetId::DOT, 900, proof.clone()),
+            Error::<Test>::InvalidBalanceProof
+        );
+        assert_ok!(PolkadexOcex::exodus_claim(Origin::signed(ALICE), AssetId::DOT, 1_000, proof.clone()));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 10_000);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &PolkadexOcex::get_account()), 0);
+        assert_noop!(
+            PolkadexOcex::exodus_claim(Origin::signed(ALICE), AssetId::DOT, 1_000, proof),
+            Error::<Test>::ExodusAlreadyClaimed
+        );
+    });
+}
+
+#[test]
+fn test_exodus_claim_leaves_open_withdrawals() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        let proof = commit_two_balances(1, 1_000, 0);
+        run_to_block(102);
+        assert_eq!(PolkadexOcex::exodus_mode(), Some(1));
+        assert_ok!(PolkadexOcex::exodus_claim(Origin::signed(ALICE), AssetId::DOT, 1_000, proof));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_600);
+        // The open request is paid once the enclave is back, without paying the claim twice
+        assert_ok!(PolkadexOcex::exit_exodus_mode(Origin::signed(GOVERNANCE), ENCLAVE));
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 10_000);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &PolkadexOcex::get_account()), 0);
+    });
+}
+
+#[test]
+fn test_exodus_claim_of_withdrawn_balance_is_refused() {
+    new_tester_with_enclave().execute_with(|| {
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        let proof = commit_two_balances(1, 400, 600);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        run_to_block(102);
+        assert_noop!(
+            PolkadexOcex::exodus_claim(Origin::signed(ALICE), AssetId::DOT, 400, proof),
+            Error::<Test>::NothingToClaim
+        );
+    });
+}
+
+#[test]
+fn test_enclave_without_heartbeat_goes_stale() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_eq!(PolkadexOcex::live_enclaves(), vec![ENCLAVE]);
+        run_to_block(11);
+        assert_eq!(PolkadexOcex::live_enclaves(), vec![ENCLAVE]);
+        run_to_block(12);
+        assert!(PolkadexOcex::live_enclaves().is_empty());
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000),
+            Error::<Test>::NoLiveEnclave
+        );
+        assert_noop!(
+            PolkadexOcex::heartbeat(Origin::signed(BOB)),
+            Error::<Test>::NotARegisteredEnclave
+        );
+        assert_ok!(PolkadexOcex::heartbeat(Origin::signed(ENCLAVE)));
+        assert_eq!(PolkadexOcex::enclave_heartbeat(ENCLAVE), Some(12));
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+    });
+}
+
+#[test]
+fn test_paused_operations_are_refused() {
+    new_tester_with_enclave().execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        assert_noop!(
+            PolkadexOcex::set_paused(Origin::signed(ALICE), OcexOperation::Deposit, None, true),
+            DispatchError::BadOrigin
+        );
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Deposit, None, true));
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000),
+            Error::<Test>::OperationPaused
+        );
+
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Withdraw, Some(AssetId::DOT), true));
+        assert_noop!(
+            PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, ALICE, 100),
+            Error::<Test>::OperationPaused
+        );
+
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Release, Some(AssetId::DOT), true));
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1),
+            Error::<Test>::OperationPaused
+        );
+        let paused = PolkadexOcex::paused_operations();
+        assert_eq!(paused.len(), 3);
+        assert!(paused.contains(&(OcexOperation::Deposit, None)));
+        assert!(paused.contains(&(OcexOperation::Withdraw, Some(AssetId::DOT))));
+        assert!(paused.contains(&(OcexOperation::Release, Some(AssetId::DOT))));
+
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Deposit, None, false));
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Withdraw, Some(AssetId::DOT), false));
+        assert_ok!(PolkadexOcex::set_paused(governance, OcexOperation::Release, Some(AssetId::DOT), false));
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_ok!(PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, ALICE, 100));
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+    });
+}
+
+#[test]
+fn test_deposit_rules_are_enforced() {
+    new_tester_with_enclave().execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::POLKADEX, 1_000),
+            Error::<Test>::AssetNotEnabled
+        );
+        let token = AssetId::CHAINSAFE(sp_core::H160::zero());
+        let enabled = AssetDepositConfig {
+            enabled: true,
+            min_deposit: 10,
+            max_deposit: Some(500),
+            max_per_account: Some(800),
+        };
+        assert_ok!(PolkadexOcex::set_asset_config(governance.clone(), token, enabled.clone()));
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), token, 100),
+            Error::<Test>::AssetNotVerified
+        );
+
+        assert_ok!(PolkadexOcex::set_asset_config(governance.clone(), AssetId::DOT, enabled.clone()));
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 9),
+            Error::<Test>::DepositBelowMinimum
+        );
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 501),
+            Error::<Test>::DepositAboveMaximum
+        );
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 500));
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 400),
+            Error::<Test>::AccountDepositCapExceeded
+        );
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 300));
+        assert_eq!(PolkadexOcex::account_deposits(ALICE, AssetId::DOT), 800);
+
+        // Settled withdrawals free up room under the account cap
+        let withdrawal_id = PolkadexOcex::next_withdrawal_id();
+        assert_ok!(PolkadexOcex::withdraw(Origin::signed(ALICE), AssetId::DOT, ALICE, 300));
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 300));
+
+        assert_ok!(PolkadexOcex::set_asset_config(
+            governance,
+            AssetId::DOT,
+            AssetDepositConfig { enabled: false, ..enabled }
+        ));
+        assert_noop!(
+            PolkadexOcex::deposit(Origin::signed(BOB), AssetId::DOT, 100),
+            Error::<Test>::AssetNotEnabled
+        );
+    });
+}
+
+#[test]
+fn test_outflow_limit_rolls_over_buckets() {
+    new_tester_with_enclave().execute_with(|| {
+        let limit = OutflowLimit { max_amount: 500, period: 10 };
+        assert_ok!(PolkadexOcex::set_outflow_limit(Origin::signed(GOVERNANCE), AssetId::DOT, Some(limit)));
+        let first = deposit_and_withdraw(ALICE, 400);
+        let second = deposit_and_withdraw(ALICE, 200);
+        let third = deposit_and_withdraw(ALICE, 200);
+        let fourth = deposit_and_withdraw(ALICE, 200);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), first, 1));
+        assert_eq!(withdrawal_status(first), WithdrawalStatus::Settled(1));
+
+        run_to_block(9);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), second, 2));
+        assert_eq!(withdrawal_status(second), WithdrawalStatus::Queued);
+
+        // A fixed window starting in block 1 would have reset by now
+        run_to_block(11);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), third, 3));
+        assert_eq!(withdrawal_status(third), WithdrawalStatus::Queued);
+
+        run_to_block(12);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), fourth, 4));
+        assert_eq!(withdrawal_status(fourth), WithdrawalStatus::Settled(12));
+        assert_eq!(
+            PolkadexOcex::outflow_buckets(AssetId::DOT),
+            vec![OutflowBucket { start: 12, released: 200 }]
+        );
+    });
+}
+
+#[test]
+fn test_queued_release_is_not_approved_during_exodus() {
+    new_tester_with_enclave().execute_with(|| {
+        let limit = OutflowLimit { max_amount: 300, period: 10 };
+        assert_ok!(PolkadexOcex::set_outflow_limit(Origin::signed(GOVERNANCE), AssetId::DOT, Some(limit)));
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Queued);
+        run_to_block(21);
+        ExodusMode::put(1);
+        assert_noop!(
+            PolkadexOcex::approve_queued_release(Origin::signed(GOVERNANCE), withdrawal_id),
+            Error::<Test>::ExodusModeActive
+        );
+        ExodusMode::kill();
+        assert_ok!(PolkadexOcex::approve_queued_release(Origin::signed(GOVERNANCE), withdrawal_id));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Settled(21));
+    });
+}
+
+/// Registers a second static enclave running the allowed build and requires two approvals
+fn second_enclave() -> u64 {
+    let governance = Origin::signed(GOVERNANCE);
+    assert_ok!(PolkadexOcex::add_static_enclave(governance.clone(), 11, MR_ENCLAVE));
+    assert_ok!(PolkadexOcex::set_release_threshold(governance, 2));
+    11
+}
+
+#[test]
+fn test_release_needs_threshold_of_distinct_enclaves() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::set_release_threshold(Origin::signed(GOVERNANCE), 0),
+            Error::<Test>::InvalidReleaseThreshold
+        );
+        let other = second_enclave();
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Pending);
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 2),
+            Error::<Test>::ReleaseAlreadyApproved
+        );
+        assert_ok!(PolkadexOcex::release(Origin::signed(other), withdrawal_id, 1));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Settled(1));
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_400);
+    });
+}
+
+#[test]
+fn test_release_approvals_expire() {
+    new_tester_with_enclave().execute_with(|| {
+        let other = second_enclave();
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        let withdrawal_ids: &[WithdrawalId] = &[withdrawal_id];
+        let payload = BlakeTwo256::hash_of(&withdrawal_ids);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+        assert!(PolkadexOcex::release_approval(payload).is_some());
+        run_to_block(6);
+        assert_eq!(PolkadexOcex::release_approval(payload), None);
+
+        // The late approval starts a new round instead of completing the expired one
+        assert_ok!(PolkadexOcex::release(Origin::signed(other), withdrawal_id, 1));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Pending);
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 2));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Settled(6));
+    });
+}
+
+const SUCCESSOR: MrEnclave = [8u8; 32];
+
+/// Registers enclave 11 running the successor build and announces the handover to it
+fn announce_handover() -> u64 {
+    let governance = Origin::signed(GOVERNANCE);
+    assert_ok!(PolkadexOcex::allow_mrenclave(governance.clone(), SUCCESSOR, None));
+    assert_ok!(PolkadexOcex::add_static_enclave(governance, 11, SUCCESSOR));
+    assert_ok!(PolkadexOcex::announce_successor(Origin::signed(ENCLAVE), SUCCESSOR));
+    11
+}
+
+#[test]
+fn test_acknowledged_handover_retires_old_build() {
+    new_tester_with_enclave().execute_with(|| {
+        let successor = announce_handover();
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(successor), withdrawal_id, 1),
+            Error::<Test>::HandoverNotAcknowledged
+        );
+        commit_two_balances(1, 600, 0);
+        let root = PolkadexOcex::balance_snapshot(1).unwrap();
+        assert_noop!(
+            PolkadexOcex::acknowledge_handover(Origin::signed(successor), H256::zero()),
+            Error::<Test>::StateRootMismatch
+        );
+        assert_ok!(PolkadexOcex::acknowledge_handover(Origin::signed(successor), root));
+        assert_eq!(PolkadexOcex::pending_handover(), None);
+        assert_eq!(
+            PolkadexOcex::allowed_mrenclave(MR_ENCLAVE).unwrap().expires_at,
+            Some(11)
+        );
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1),
+            Error::<Test>::EnclaveRetired
+        );
+        assert_ok!(PolkadexOcex::release(Origin::signed(successor), withdrawal_id, 1));
+        assert_eq!(withdrawal_status(withdrawal_id), WithdrawalStatus::Settled(1));
+    });
+}
+
+#[test]
+fn test_handover_rollback_expires_successor_build() {
+    new_tester_with_enclave().execute_with(|| {
+        let successor = announce_handover();
+        let withdrawal_id = deposit_and_withdraw(ALICE, 400);
+        run_to_block(11);
+        assert!(PolkadexOcex::pending_handover().is_some());
+        run_to_block(12);
+        assert_eq!(PolkadexOcex::pending_handover(), None);
+        assert_eq!(
+            PolkadexOcex::allowed_mrenclave(SUCCESSOR).unwrap().expires_at,
+            Some(12)
+        );
+        assert_noop!(
+            PolkadexOcex::release(Origin::signed(successor), withdrawal_id, 1),
+            Error::<Test>::MrEnclaveExpired
+        );
+        assert_ok!(PolkadexOcex::release(Origin::signed(ENCLAVE), withdrawal_id, 1));
+    });
+}
+
+#[test]
+fn test_cancelled_handover_expires_successor_build() {
+    new_tester_with_enclave().execute_with(|| {
+        announce_handover();
+        assert_noop!(
+            PolkadexOcex::cancel_handover(Origin::signed(ALICE)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(PolkadexOcex::cancel_handover(Origin::signed(GOVERNANCE)));
+        assert_eq!(PolkadexOcex::pending_handover(), None);
+        assert_eq!(
+            PolkadexOcex::allowed_mrenclave(SUCCESSOR).unwrap().expires_at,
+            Some(1)
+        );
+        assert_noop!(
+            PolkadexOcex::announce_successor(Origin::signed(ENCLAVE), SUCCESSOR),
+            Error::<Test>::MrEnclaveExpired
+        );
+    });
+}
+
+const MARKET: TradingPair = TradingPair {
+    base: AssetId::POLKADEX,
+    quote: AssetId::DOT,
+};
+
+fn open_market() {
+    let governance = Origin::signed(GOVERNANCE);
+    assert_ok!(PolkadexOcex::register_market(governance.clone(), MARKET, 10, 100, 50));
+    assert_ok!(PolkadexOcex::set_market_status(governance, MARKET, MarketStatus::Open));
+}
+
+fn fill(market: TradingPair) -> Fill<u64, Balance> {
+    Fill {
+        market,
+        price: 20,
+        quantity: 300,
+        maker: ALICE,
+        taker: BOB,
+        fee: 6,
+    }
+}
+
+#[test]
+fn test_trade_batches_are_recorded() {
+    new_tester_with_enclave().execute_with(|| {
+        open_market();
+        let fills = vec![fill(MARKET), fill(MARKET)];
+        assert_noop!(
+            PolkadexOcex::post_trades(Origin::signed(BOB), fills.clone()),
+            Error::<Test>::NotARegisteredEnclave
+        );
+        assert_ok!(PolkadexOcex::post_trades(Origin::signed(ENCLAVE), fills.clone()));
+        assert_ok!(PolkadexOcex::post_trades(Origin::signed(ENCLAVE), vec![fill(MARKET)]));
+        assert_eq!(PolkadexOcex::next_trade_batch_id(), 2);
+        assert_eq!(PolkadexOcex::trade_batch(0), Some(BlakeTwo256::hash_of(&fills)));
+        let posted = mock::Event::from(crate::Event::<Test>::TradeBatchPosted(0, BlakeTwo256::hash_of(&fills), 2));
+        assert!(System::events().iter().any(|record| record.event == posted));
+    });
+}
+
+#[test]
+fn test_invalid_trade_batches_are_refused() {
+    new_tester_with_enclave().execute_with(|| {
+        assert_noop!(
+            PolkadexOcex::post_trades(Origin::signed(ENCLAVE), vec![]),
+            Error::<Test>::EmptyTradeBatch
+        );
+        assert_noop!(
+            PolkadexOcex::post_trades(Origin::signed(ENCLAVE), vec![fill(MARKET); 11]),
+            Error::<Test>::TradeBatchTooLarge
+        );
+        assert_noop!(
+            PolkadexOcex::post_trades(Origin::signed(ENCLAVE), vec![fill(MARKET)]),
+            Error::<Test>::MarketNotFound
+        );
+        assert_ok!(PolkadexOcex::register_market(Origin::signed(GOVERNANCE), MARKET, 10, 100, 50));
+        assert_noop!(
+            PolkadexOcex::post_trades(Origin::signed(ENCLAVE), vec![fill(MARKET)]),
+            Error::<Test>::MarketNotOpen
+        );
+    });
+}
+
+#[test]
+fn test_fees_are_bounded_by_custody_surplus() {
+    new_tester_with_enclave().execute_with(|| {
+        let enclave = Origin::signed(ENCLAVE);
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_ok!(PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 800)], 1));
+        assert_noop!(
+            PolkadexOcex::report_fees(enclave.clone(), vec![(AssetId::DOT, 201)]),
+            Error::<Test>::FeesExceedSurplus
+        );
+        assert_noop!(
+            PolkadexOcex::report_fees(enclave.clone(), vec![(AssetId::POLKADEX, 1)]),
+            Error::<Test>::FeesExceedSurplus
+        );
+        assert_ok!(PolkadexOcex::report_fees(enclave.clone(), vec![(AssetId::DOT, 150)]));
+        assert_eq!(PolkadexOcex::accrued_fees(AssetId::DOT), 150);
+        assert_noop!(
+            PolkadexOcex::report_fees(enclave, vec![(AssetId::DOT, 51)]),
+            Error::<Test>::FeesExceedSurplus
+        );
+    });
+}
+
+#[test]
+fn test_fees_accrue_once_threshold_reports_them() {
+    new_tester_with_enclave().execute_with(|| {
+        let other = second_enclave();
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_ok!(PolkadexOcex::report_liabilities(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 800)], 1));
+        assert_ok!(PolkadexOcex::report_liabilities(Origin::signed(other), vec![(AssetId::DOT, 800)], 1));
+        assert_ok!(PolkadexOcex::report_fees(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 150)]));
+        assert_eq!(PolkadexOcex::accrued_fees(AssetId::DOT), 0);
+        assert_noop!(
+            PolkadexOcex::report_fees(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 150)]),
+            Error::<Test>::ReleaseAlreadyApproved
+        );
+        assert_ok!(PolkadexOcex::report_fees(Origin::signed(other), vec![(AssetId::DOT, 100)]));
+        assert_eq!(PolkadexOcex::accrued_fees(AssetId::DOT), 0);
+        assert_ok!(PolkadexOcex::report_fees(Origin::signed(other), vec![(AssetId::DOT, 150)]));
+        assert_eq!(PolkadexOcex::accrued_fees(AssetId::DOT), 150);
+    });
+}
+
+#[test]
+fn test_liabilities_apply_once_threshold_reports_them() {
+    new_tester_with_enclave().execute_with(|| {
+        let other = second_enclave();
+        assert_ok!(PolkadexOcex::report_liabilities(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 800)], 1));
+        assert!(PolkadexOcex::liabilities().is_empty());
+        assert_noop!(
+            PolkadexOcex::report_liabilities(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 800)], 1),
+            Error::<Test>::ReleaseAlreadyApproved
+        );
+        // A different report collects its own approvals
+        assert_ok!(PolkadexOcex::report_liabilities(Origin::signed(other), vec![(AssetId::DOT, 900)], 1));
+        assert!(PolkadexOcex::liabilities().is_empty());
+        assert_ok!(PolkadexOcex::report_liabilities(Origin::signed(other), vec![(AssetId::DOT, 800)], 1));
+        assert_eq!(PolkadexOcex::liabilities(), vec![(AssetId::DOT, 800)]);
+        assert_eq!(PolkadexOcex::liabilities_nonce(), 1);
+        assert_noop!(
+            PolkadexOcex::report_liabilities(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 900)], 1),
+            Error::<Test>::LiabilitiesNonceMismatch
+        );
+    });
+}
+
+#[test]
+fn test_fee_sweep_is_checked() {
+    new_tester_with_enclave().execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        let enclave = Origin::signed(ENCLAVE);
+        assert_ok!(PolkadexOcex::deposit(Origin::signed(ALICE), AssetId::DOT, 1_000));
+        assert_ok!(PolkadexOcex::report_liabilities(enclave.clone(), vec![(AssetId::DOT, 800)], 1));
+        assert_ok!(PolkadexOcex::report_fees(enclave.clone(), vec![(AssetId::DOT, 150)]));
+
+        assert_ok!(PolkadexOcex::set_paused(governance.clone(), OcexOperation::Release, Some(AssetId::DOT), true));
+        assert_noop!(
+            PolkadexOcex::sweep_fees(Origin::signed(BOB), AssetId::DOT),
+            Error::<Test>::OperationPaused
+        );
+        assert_ok!(PolkadexOcex::set_paused(governance, OcexOperation::Release, Some(AssetId::DOT), false));
+
+        ExodusMode::put(1);
+        assert_noop!(
+            PolkadexOcex::sweep_fees(Origin::signed(BOB), AssetId::DOT),
+            Error::<Test>::ExodusModeActive
+        );
+        ExodusMode::kill();
+
+        assert_ok!(PolkadexOcex::report_liabilities(enclave, vec![(AssetId::DOT, 900)], 2));
+        assert_noop!(
+            PolkadexOcex::sweep_fees(Origin::signed(BOB), AssetId::DOT),
+            Error::<Test>::FeesExceedSurplus
+        );
+        assert_ok!(PolkadexOcex::report_liabilities(Origin::signed(ENCLAVE), vec![(AssetId::DOT, 850)], 3));
+        assert_ok!(PolkadexOcex::sweep_fees(Origin::signed(BOB), AssetId::DOT));
+        assert_eq!(PolkadexOcex::accrued_fees(AssetId::DOT), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &TreasuryModuleAccount::get()), 150);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &PolkadexOcex::get_account()), 850);
+    });
+}
+
+#[test]
+fn test_markets_are_registered_by_governance() {
+    new_tester().execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        assert_noop!(
+            PolkadexOcex::register_market(Origin::signed(ALICE), MARKET, 10, 100, 50),
+            DispatchError::BadOrigin
+        );
+        let same_assets = TradingPair { base: AssetId::DOT, quote: AssetId::DOT };
+        assert_noop!(
+            PolkadexOcex::register_market(governance.clone(), same_assets, 10, 100, 50),
+            Error::<Test>::InvalidMarket
+        );
+        assert_noop!(
+            PolkadexOcex::register_market(governance.clone(), MARKET, 0, 100, 50),
+            Error::<Test>::InvalidMarket
+        );
+        assert_noop!(
+            PolkadexOcex::register_market(governance.clone(), MARKET, 10, 0, 50),
+            Error::<Test>::InvalidMarket
+        );
+        assert_ok!(PolkadexOcex::register_market(governance.clone(), MARKET, 10, 100, 50));
+        assert_noop!(
+            PolkadexOcex::register_market(governance, MARKET, 10, 100, 50),
+            Error::<Test>::MarketAlreadyExists
+        );
+        let info = MarketInfo {
+            tick_size: 10,
+            lot_size: 100,
+            min_notional: 50,
+            status: MarketStatus::PreOpen,
+        };
+        assert_eq!(PolkadexOcex::markets(), vec![(MARKET, info.clone())]);
+        assert_eq!(<PolkadexOcex as MarketRegistry<Balance>>::market_info(&MARKET), Some(info));
+    });
+}
+
+#[test]
+fn test_delisted_markets_are_frozen() {
+    new_tester().execute_with(|| {
+        let governance = Origin::signed(GOVERNANCE);
+        assert_noop!(
+            PolkadexOcex::update_market(governance.clone(), MARKET, 10, 100, 50),
+            Error::<Test>::MarketNotFound
+        );
+        assert_noop!(
+            PolkadexOcex::set_market_status(governance.clone(), MARKET, MarketStatus::Open),
+            Error::<Test>::MarketNotFound
+        );
+        open_market();
+        assert_ok!(PolkadexOcex::update_market(governance.clone(), MARKET, 5, 200, 100));
+        assert_eq!(PolkadexOcex::market(MARKET).unwrap().lot_size, 200);
+        assert_ok!(PolkadexOcex::set_market_status(governance.clone(), MARKET, MarketStatus::Halted));
+        assert_ok!(PolkadexOcex::set_market_status(governance.clone(), MARKET, MarketStatus::Delisted));
+        assert_noop!(
+            PolkadexOcex::set_market_status(governance.clone(), MARKET, MarketStatus::Open),
+            Error::<Test>::MarketDelisted
+        );
+        assert_noop!(
+            PolkadexOcex::update_market(governance, MARKET, 10, 100, 50),
+            Error::<Test>::MarketDelisted
+        );
+    });
+}
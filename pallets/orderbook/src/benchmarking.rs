@@ -0,0 +1,178 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Benchmarks for the orderbook pallet
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+const MARKET: TradingPair = TradingPair {
+    base: AssetId::POLKADEX,
+    quote: AssetId::DOT,
+};
+
+fn balance<T: Config>(value: u32) -> T::Balance {
+    value.into()
+}
+
+/// Lists the benchmarked market and returns the rules it trades with
+fn open_market<T: Config>() -> Result<MarketInfo<T::Balance>, &'static str> {
+    T::Markets::register_market(
+        MARKET,
+        MarketInfo {
+            tick_size: balance::<T>(1),
+            lot_size: balance::<T>(1),
+            min_notional: balance::<T>(1),
+            status: MarketStatus::Open,
+        },
+    );
+    T::Markets::market_info(&MARKET).ok_or("market not listed")
+}
+
+/// Funds `who` with both assets of the benchmarked market
+fn fund<T: Config>(who: &T::AccountId) -> DispatchResult {
+    T::Currency::deposit(MARKET.base, who, balance::<T>(1_000_000_000))?;
+    T::Currency::deposit(MARKET.quote, who, balance::<T>(1_000_000_000))
+}
+
+/// Lowest price which meets the minimum notional for one lot
+fn base_price<T: Config>(info: &MarketInfo<T::Balance>) -> T::Balance {
+    (info.min_notional / info.tick_size + One::one()) * info.tick_size
+}
+
+/// Rests `count` asks of one lot from different makers, one price level each while levels
+/// are available
+fn rest_asks<T: Config>(
+    info: &MarketInfo<T::Balance>,
+    count: u32,
+) -> Result<T::Balance, &'static str> {
+    let mut highest = base_price::<T>(info);
+    for index in 0 .. count {
+        let maker: T::AccountId = account("maker", index, SEED);
+        fund::<T>(&maker)?;
+        let level = index % T::MaxPriceLevels::get();
+        let price = base_price::<T>(info) + info.tick_size * balance::<T>(level);
+        highest = highest.max(price);
+        Module::<T>::place_order(
+            RawOrigin::Signed(maker).into(),
+            MARKET,
+            OrderSide::Ask,
+            OrderType::Limit { price, time_in_force: TimeInForce::GoodTillCancelled },
+            info.lot_size,
+        )?;
+    }
+    Ok(highest)
+}
+
+/// Schedules `count` other slices in each of the `blocks` blocks after the current one
+fn fill_twap_schedule<T: Config>(blocks: u32, count: u32) {
+    let now = <system::Module<T>>::block_number();
+    for offset in 1 ..= blocks {
+        <TwapSchedule<T>>::insert(now + offset.into(), sp_std::vec![OrderId::MAX; count as usize]);
+    }
+}
+
+benchmarks! {
+    place_order {
+        let m in 0 .. T::MaxMatchesPerOrder::get();
+        let info = open_market::<T>()?;
+        let limit = rest_asks::<T>(&info, m)?;
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller)?;
+        let order_id = Module::<T>::next_order_id();
+        // One lot more than the book holds, so the order also rests
+        let quantity = info.lot_size * balance::<T>(m + 1);
+        let order_type = OrderType::Limit { price: limit, time_in_force: TimeInForce::GoodTillCancelled };
+    }: _(RawOrigin::Signed(caller), MARKET, OrderSide::Bid, order_type, quantity)
+    verify {
+        assert!(Module::<T>::price_levels(MARKET, OrderSide::Ask).is_empty());
+        assert!(Module::<T>::order(order_id).is_some());
+    }
+
+    cancel_order {
+        let info = open_market::<T>()?;
+        let price = base_price::<T>(&info);
+        for index in 1 .. T::MaxOrdersPerLevel::get() {
+            let maker: T::AccountId = account("maker", index, SEED);
+            fund::<T>(&maker)?;
+            Module::<T>::place_order(
+                RawOrigin::Signed(maker).into(),
+                MARKET,
+                OrderSide::Ask,
+                OrderType::Limit { price, time_in_force: TimeInForce::GoodTillCancelled },
+                info.lot_size,
+            )?;
+        }
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller)?;
+        let order_id = Module::<T>::next_order_id();
+        Module::<T>::place_order(
+            RawOrigin::Signed(caller.clone()).into(),
+            MARKET,
+            OrderSide::Ask,
+            OrderType::Limit { price, time_in_force: TimeInForce::GoodTillCancelled },
+            info.lot_size,
+        )?;
+    }: _(RawOrigin::Signed(caller), order_id)
+    verify {
+        assert!(Module::<T>::order(order_id).is_none());
+    }
+
+    place_twap_order {
+        let info = open_market::<T>()?;
+        // Only the last block the first slice may move to has room left
+        let interval: u32 = T::MinTwapInterval::get().unique_saturated_into();
+        fill_twap_schedule::<T>(interval.saturating_sub(1), T::MaxTwapSlicesPerBlock::get());
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller)?;
+        let order_id = Module::<T>::next_order_id();
+        let quantity = info.lot_size * balance::<T>(2);
+        let limit = Some(base_price::<T>(&info));
+    }: _(RawOrigin::Signed(caller), MARKET, OrderSide::Bid, quantity, limit, 2, T::MinTwapInterval::get())
+    verify {
+        assert!(Module::<T>::twap_order(order_id).is_some());
+    }
+
+    cancel_twap_order {
+        let info = open_market::<T>()?;
+        // The slice shares its block with as many others as fit
+        fill_twap_schedule::<T>(1, T::MaxTwapSlicesPerBlock::get().saturating_sub(1));
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller)?;
+        let order_id = Module::<T>::next_order_id();
+        Module::<T>::place_twap_order(
+            RawOrigin::Signed(caller.clone()).into(),
+            MARKET,
+            OrderSide::Bid,
+            info.lot_size * balance::<T>(2),
+            Some(base_price::<T>(&info)),
+            2,
+            T::MinTwapInterval::get(),
+        )?;
+    }: _(RawOrigin::Signed(caller), order_id)
+    verify {
+        assert!(Module::<T>::twap_order(order_id).is_none());
+    }
+}
+
+impl_benchmark_test_suite!(Module, crate::mock::new_tester(), crate::mock::Test);
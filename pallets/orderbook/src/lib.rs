@@ -0,0 +1,978 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-chain limit order book used as a fallback when no enclave is available.
+//!
+//! Orders are matched with price-time priority. Prices are quoted in quote asset per lot of
+//! base asset, so the value of a trade is `price * quantity / lot_size`. Resting orders keep the
+//! lot size and the funds reserved when they were placed, so later changes of the market rules
+//! do not affect them.
+//!
+//! Stop orders wait off the book until the last traded price of their market reaches the stop
+//! price and are placed at the start of the next block. Their funds are reserved while they wait,
+//! bids of stop loss orders at their stop price, and released when they are placed.
+//!
+//! TWAP orders are split into equal slices which are placed as market orders every `interval`
+//! blocks. Their funds are reserved up front, bids at their limit price, and each slice releases
+//! its share when it is placed. At most `MaxTwapSlicesPerBlock` slices are scheduled in a block,
+//! a slice which finds its block full moves to one of the following blocks.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+    storage::with_transaction,
+    traits::{BalanceStatus, Get},
+    transactional,
+    weights::Weight,
+};
+use frame_system as system;
+use frame_system::ensure_signed;
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
+use polkadex_primitives::assets::AssetId;
+use polkadex_primitives::markets::{MarketInfo, MarketRegistry, MarketStatus, TradingPair};
+use polkadex_primitives::orders::{OrderSide, OrderType, TimeInForce};
+use sp_runtime::traits::{CheckedMul, One, Saturating, UniqueSaturatedInto, Zero};
+use sp_runtime::TransactionOutcome;
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod test;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+pub type OrderId = u64;
+
+pub trait Config: system::Config + orml_tokens::Config<CurrencyId = AssetId> {
+    type Event: From<Event<Self>> + Into<<Self as system::Config>::Event>;
+    /// Currency used to reserve and exchange the funds of orders
+    type Currency: MultiReservableCurrency<
+        Self::AccountId,
+        CurrencyId = AssetId,
+        Balance = Self::Balance,
+    >;
+    /// Trading rules of the markets
+    type Markets: MarketRegistry<Self::Balance>;
+    /// Maximum number of resting orders an incoming order is matched against
+    type MaxMatchesPerOrder: Get<u32>;
+    /// Maximum number of price levels on each side of a market
+    type MaxPriceLevels: Get<u32>;
+    /// Maximum number of resting orders at one price level
+    type MaxOrdersPerLevel: Get<u32>;
+    /// Maximum number of waiting stop orders on each side of a market
+    type MaxStopOrders: Get<u32>;
+    /// Maximum number of stop orders placed in one block
+    type MaxStopTriggersPerBlock: Get<u32>;
+    /// Minimum number of blocks between two slices of a TWAP order
+    type MinTwapInterval: Get<Self::BlockNumber>;
+    /// Maximum number of blocks a TWAP order is executed over
+    type MaxTwapDuration: Get<Self::BlockNumber>;
+    /// Maximum number of TWAP slices placed in one block, the others are deferred
+    type MaxTwapSlicesPerBlock: Get<u32>;
+    /// Weight information for extrinsics of this pallet
+    type WeightInfo: WeightInfo;
+}
+
+/// Order resting on the book
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Order<AccountId, Balance, BlockNumber> {
+    pub owner: AccountId,
+    pub market: TradingPair,
+    pub side: OrderSide,
+    pub price: Balance,
+    /// Quantity of base asset not filled yet
+    pub remaining: Balance,
+    /// Lot size of the market when the order was placed, its fills are valued with it
+    pub lot_size: Balance,
+    /// Funds still reserved for the unfilled quantity
+    pub reserved: Balance,
+    pub placed_at: BlockNumber,
+}
+
+pub type OrderOf<T> = Order<
+    <T as system::Config>::AccountId,
+    <T as orml_tokens::Config>::Balance,
+    <T as system::Config>::BlockNumber,
+>;
+
+/// Order waiting for the last traded price to reach its stop price
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct StopOrder<AccountId, Balance> {
+    pub owner: AccountId,
+    pub market: TradingPair,
+    pub side: OrderSide,
+    pub stop_price: Balance,
+    /// Limit price of the placed order, a market order if `None`
+    pub price: Option<Balance>,
+    pub quantity: Balance,
+    /// Funds reserved until the order triggers or is cancelled
+    pub reserved: Balance,
+}
+
+pub type StopOrderOf<T> =
+    StopOrder<<T as system::Config>::AccountId, <T as orml_tokens::Config>::Balance>;
+
+/// Parent order executed as market orders spread over time
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct TwapOrder<AccountId, Balance, BlockNumber> {
+    pub owner: AccountId,
+    pub market: TradingPair,
+    pub side: OrderSide,
+    /// Worst price the slices trade at, any price if `None`
+    pub limit: Option<Balance>,
+    pub quantity: Balance,
+    /// Quantity of each slice, the last slice takes the rest
+    pub slice_quantity: Balance,
+    pub slices: u32,
+    pub executed_slices: u32,
+    /// Quantity traded by the executed slices
+    pub filled: Balance,
+    pub interval: BlockNumber,
+    /// Lot size of the market when the order was placed, values the funds of bids
+    pub lot_size: Balance,
+    /// Funds still reserved for the slices not placed yet
+    pub reserved: Balance,
+    /// Block the next slice is scheduled in
+    pub next_slice_at: BlockNumber,
+}
+
+pub type TwapOrderOf<T> = TwapOrder<
+    <T as system::Config>::AccountId,
+    <T as orml_tokens::Config>::Balance,
+    <T as system::Config>::BlockNumber,
+>;
+
+decl_storage! {
+    trait Store for Module<T: Config> as PolkadexOrderbook {
+        /// Identifier of the next order
+        NextOrderId get(fn next_order_id): OrderId;
+        /// Orders resting on the book
+        Orders get(fn order): map hasher(twox_64_concat) OrderId => Option<OrderOf<T>>;
+        /// Price levels of one side of a market, best price first
+        PriceLevels get(fn price_levels): double_map hasher(blake2_128_concat) TradingPair, hasher(twox_64_concat) OrderSide => Vec<T::Balance>;
+        /// Orders at a price level in time priority
+        LevelOrders get(fn level_orders): double_map hasher(blake2_128_concat) (TradingPair, OrderSide), hasher(blake2_128_concat) T::Balance => Vec<OrderId>;
+        /// Price of the most recent trade of a market
+        LastTradedPrice get(fn last_traded_price): map hasher(blake2_128_concat) TradingPair => Option<T::Balance>;
+        /// Stop orders waiting to be placed
+        StopOrders get(fn stop_order): map hasher(twox_64_concat) OrderId => Option<StopOrderOf<T>>;
+        /// Waiting stop orders of one side of a market, the first to be triggered first
+        StopQueue get(fn stop_queue): double_map hasher(blake2_128_concat) TradingPair, hasher(twox_64_concat) OrderSide => Vec<(T::Balance, OrderId)>;
+        /// Markets whose last traded price moved since their stop orders were checked
+        PriceMovedMarkets get(fn price_moved_markets): Vec<TradingPair>;
+        /// TWAP orders with slices left to execute
+        TwapOrders get(fn twap_order): map hasher(twox_64_concat) OrderId => Option<TwapOrderOf<T>>;
+        /// TWAP orders whose next slice is due at a block
+        TwapSchedule get(fn twap_schedule): map hasher(twox_64_concat) T::BlockNumber => Vec<OrderId>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        <T as system::Config>::AccountId,
+        <T as orml_tokens::Config>::Balance,
+        <T as system::Config>::BlockNumber
+    {
+        /// Order rests on the book (order, owner, market, side, price, quantity)
+        OrderPlaced(OrderId, AccountId, TradingPair, OrderSide, Balance, Balance),
+        /// Incoming order traded against a resting order
+        /// (market, maker order, maker, taker order, taker, price, quantity)
+        OrderFilled(TradingPair, OrderId, AccountId, OrderId, AccountId, Balance, Balance),
+        /// Order removed from the book by its owner
+        OrderCancelled(OrderId, AccountId),
+        /// Unfilled quantity of an incoming order which did not rest on the book
+        OrderExpired(OrderId, AccountId, Balance),
+        /// Stop order waits for its stop price (order, owner, market, side, stop price, quantity)
+        StopOrderPlaced(OrderId, AccountId, TradingPair, OrderSide, Balance, Balance),
+        /// Stop price reached and the order was placed
+        StopOrderTriggered(OrderId, AccountId),
+        /// Stop price reached but the order could not be placed
+        StopOrderFailed(OrderId, AccountId, DispatchError),
+        /// TWAP order accepted (order, owner, market, side, quantity, slices, interval)
+        TwapOrderPlaced(OrderId, AccountId, TradingPair, OrderSide, Balance, u32, BlockNumber),
+        /// Slice of a TWAP order placed (order, slice, child order, slice filled, total filled)
+        TwapSliceExecuted(OrderId, u32, OrderId, Balance, Balance),
+        /// Slice of a TWAP order could not be placed (order, slice, error)
+        TwapSliceFailed(OrderId, u32, DispatchError),
+        /// All slices of a TWAP order executed (order, owner, total filled)
+        TwapOrderCompleted(OrderId, AccountId, Balance),
+        /// TWAP order cancelled by its owner before its last slice (order, owner, total filled)
+        TwapOrderCancelled(OrderId, AccountId, Balance),
+        /// TWAP order ended early as no block near its next slice had room (order, owner, total filled)
+        TwapOrderStopped(OrderId, AccountId, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Config> {
+        MarketNotFound,
+        MarketNotOpen,
+        /// Price must be a non-zero multiple of the tick size
+        InvalidPrice,
+        /// Quantity must be a non-zero multiple of the lot size
+        InvalidQuantity,
+        /// Order value is below the minimum notional of the market
+        BelowMinNotional,
+        OrderNotFound,
+        NotTheOwner,
+        /// Reserved funds of a resting order do not cover the trade
+        InsufficientReserve,
+        TooManyPriceLevels,
+        TooManyOrdersAtLevel,
+        /// Post-only order would trade on arrival
+        PostOnlyWouldCross,
+        /// Fill-or-kill order cannot be filled in full
+        FillOrKillNotFilled,
+        /// Last traded price already reached the stop price
+        StopPriceReached,
+        TooManyStopOrders,
+        /// Each slice of a TWAP order must trade at least one lot
+        InvalidTwapSlices,
+        TwapIntervalTooShort,
+        TwapDurationTooLong,
+        /// TWAP bids need a limit price to value the funds they reserve
+        TwapBidWithoutLimit,
+        /// No block near the first slice has room for another TWAP slice
+        TwapScheduleFull,
+        Overflow,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Config> for enum Call where
+    origin: T::Origin {
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::trigger_stop_orders().saturating_add(Self::execute_twap_slices(n))
+        }
+
+        /// Place Order
+        /// Matches the order against the book, stop orders wait for their stop price
+        #[weight = <T as Config>::WeightInfo::place_order(T::MaxMatchesPerOrder::get())]
+        #[transactional]
+        pub fn place_order(origin, market: TradingPair, side: OrderSide, order_type: OrderType<T::Balance>, quantity: T::Balance) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            let info = Self::open_market(&market)?;
+            ensure!(
+                !quantity.is_zero() && (quantity % info.lot_size).is_zero(),
+                Error::<T>::InvalidQuantity
+            );
+            let order_id = Self::next_order_id();
+            NextOrderId::put(order_id.checked_add(1).ok_or(Error::<T>::Overflow)?);
+            match order_type {
+                OrderType::Limit { price, time_in_force } => {
+                    Self::ensure_valid_price(price, quantity, &info)?;
+                    Self::execute_order(&who, order_id, market, side, Some(price), time_in_force, quantity, &info)
+                }
+                OrderType::Market => {
+                    Self::execute_order(&who, order_id, market, side, None, TimeInForce::ImmediateOrCancel, quantity, &info)
+                }
+                OrderType::StopLimit { stop_price, price } => {
+                    Self::ensure_valid_price(stop_price, quantity, &info)?;
+                    Self::ensure_valid_price(price, quantity, &info)?;
+                    Self::place_stop_order(who, order_id, market, side, stop_price, Some(price), quantity, info.lot_size)
+                }
+                OrderType::StopLoss { stop_price } => {
+                    Self::ensure_valid_price(stop_price, quantity, &info)?;
+                    Self::place_stop_order(who, order_id, market, side, stop_price, None, quantity, info.lot_size)
+                }
+            }
+        }
+
+        /// Cancel Order
+        /// Removes a resting order and unreserves its funds, or removes a waiting stop order
+        #[weight = <T as Config>::WeightInfo::cancel_order()]
+        pub fn cancel_order(origin, order_id: OrderId) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            if let Some(stop_order) = Self::stop_order(order_id) {
+                ensure!(stop_order.owner == who, Error::<T>::NotTheOwner);
+            T::Currency::unreserve(
+                Self::reserved_asset(stop_order.market, stop_order.side),
+                &who,
+                stop_order.reserved,
+            );
+                <StopQueue<T>>::mutate(stop_order.market, stop_order.side, |queue| {
+                    queue.retain(|(_, id)| *id != order_id)
+                });
+                <StopOrders<T>>::remove(order_id);
+                Self::deposit_event(RawEvent::OrderCancelled(order_id, who));
+                return Ok(());
+            }
+            let order = Self::order(order_id).ok_or(Error::<T>::OrderNotFound)?;
+            ensure!(order.owner == who, Error::<T>::NotTheOwner);
+            T::Currency::unreserve(Self::reserved_asset(order.market, order.side), &who, order.reserved);
+            Self::remove_from_level(order_id, &order);
+            <Orders<T>>::remove(order_id);
+            Self::deposit_event(RawEvent::OrderCancelled(order_id, who));
+            Ok(())
+        }
+
+        /// Place TWAP Order
+        /// Splits `quantity` into `slices` market orders placed every `interval` blocks and
+        /// reserves their funds, bids are valued at their limit price
+        #[weight = <T as Config>::WeightInfo::place_twap_order()]
+        #[transactional]
+        pub fn place_twap_order(origin, market: TradingPair, side: OrderSide, quantity: T::Balance, limit: Option<T::Balance>, slices: u32, interval: T::BlockNumber) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            let info = Self::open_market(&market)?;
+            ensure!(
+                !quantity.is_zero() && (quantity % info.lot_size).is_zero(),
+                Error::<T>::InvalidQuantity
+            );
+            if let Some(price) = limit {
+                Self::ensure_valid_price(price, quantity, &info)?;
+            }
+            let reserved = match (side, limit) {
+                (OrderSide::Bid, Some(price)) => Self::notional(price, quantity, info.lot_size)?,
+                (OrderSide::Bid, None) => return Err(Error::<T>::TwapBidWithoutLimit.into()),
+                (OrderSide::Ask, _) => quantity,
+            };
+            let lots_per_slice = (quantity / info.lot_size) / T::Balance::from(slices.max(1));
+            ensure!(slices > 0 && !lots_per_slice.is_zero(), Error::<T>::InvalidTwapSlices);
+            ensure!(interval >= T::MinTwapInterval::get(), Error::<T>::TwapIntervalTooShort);
+            ensure!(
+                interval.saturating_mul(slices.into()) <= T::MaxTwapDuration::get(),
+                Error::<T>::TwapDurationTooLong
+            );
+            let order_id = Self::next_order_id();
+            NextOrderId::put(order_id.checked_add(1).ok_or(Error::<T>::Overflow)?);
+            let first_slice_at = Self::schedule_twap_slice(
+                order_id,
+                <system::Module<T>>::block_number() + One::one(),
+            ).ok_or(Error::<T>::TwapScheduleFull)?;
+            T::Currency::reserve(Self::reserved_asset(market, side), &who, reserved)?;
+            <TwapOrders<T>>::insert(order_id, TwapOrder {
+                owner: who.clone(),
+                market,
+                side,
+                limit,
+                quantity,
+                slice_quantity: lots_per_slice * info.lot_size,
+                slices,
+                executed_slices: 0,
+                filled: Zero::zero(),
+                interval,
+                lot_size: info.lot_size,
+                reserved,
+                next_slice_at: first_slice_at,
+            });
+            Self::deposit_event(RawEvent::TwapOrderPlaced(order_id, who, market, side, quantity, slices, interval));
+            Ok(())
+        }
+
+        /// Cancel TWAP Order
+        /// Stops a TWAP order before its remaining slices are placed
+        #[weight = <T as Config>::WeightInfo::cancel_twap_order()]
+        pub fn cancel_twap_order(origin, order_id: OrderId) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            let twap = Self::twap_order(order_id).ok_or(Error::<T>::OrderNotFound)?;
+            ensure!(twap.owner == who, Error::<T>::NotTheOwner);
+            <TwapSchedule<T>>::mutate_exists(twap.next_slice_at, |due| {
+                if let Some(orders) = due {
+                    orders.retain(|id| *id != order_id);
+                    if orders.is_empty() {
+                        *due = None;
+                    }
+                }
+            });
+            T::Currency::unreserve(Self::reserved_asset(twap.market, twap.side), &who, twap.reserved);
+            <TwapOrders<T>>::remove(order_id);
+            Self::deposit_event(RawEvent::TwapOrderCancelled(order_id, who, twap.filled));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Module<T> {
+    fn open_market(market: &TradingPair) -> Result<MarketInfo<T::Balance>, Error<T>> {
+        let info = T::Markets::market_info(market).ok_or(Error::<T>::MarketNotFound)?;
+        ensure!(info.status == MarketStatus::Open, Error::<T>::MarketNotOpen);
+        Ok(info)
+    }
+
+    fn ensure_valid_price(
+        price: T::Balance,
+        quantity: T::Balance,
+        info: &MarketInfo<T::Balance>,
+    ) -> DispatchResult {
+        ensure!(
+            !price.is_zero() && (price % info.tick_size).is_zero(),
+            Error::<T>::InvalidPrice
+        );
+        ensure!(
+            Self::notional(price, quantity, info.lot_size)? >= info.min_notional,
+            Error::<T>::BelowMinNotional
+        );
+        Ok(())
+    }
+
+    /// Matches an order against the book and handles its unfilled quantity per `time_in_force`
+    #[allow(clippy::too_many_arguments)]
+    fn execute_order(
+        who: &T::AccountId,
+        order_id: OrderId,
+        market: TradingPair,
+        side: OrderSide,
+        limit: Option<T::Balance>,
+        time_in_force: TimeInForce,
+        quantity: T::Balance,
+        info: &MarketInfo<T::Balance>,
+    ) -> DispatchResult {
+        if time_in_force == TimeInForce::PostOnly {
+            ensure!(!Self::crosses_book(market, side, limit), Error::<T>::PostOnlyWouldCross);
+        }
+        let remaining = Self::match_order(who, order_id, market, side, limit, quantity)?;
+        if remaining.is_zero() {
+            return Ok(());
+        }
+        match (limit, time_in_force) {
+            (_, TimeInForce::FillOrKill) => Err(Error::<T>::FillOrKillNotFilled.into()),
+            // Matching stops at `MaxMatchesPerOrder`, a remainder which still crosses expires
+            (Some(price), TimeInForce::GoodTillCancelled)
+            | (Some(price), TimeInForce::PostOnly)
+                if !Self::crosses_book(market, side, limit) =>
+            {
+                Self::rest_order(order_id, who.clone(), market, side, price, remaining, info)
+            }
+            _ => {
+                Self::deposit_event(RawEvent::OrderExpired(order_id, who.clone(), remaining));
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether a stop order on `side` triggers at `last_price`
+    fn stop_reached(side: OrderSide, stop_price: T::Balance, last_price: T::Balance) -> bool {
+        match side {
+            OrderSide::Bid => last_price >= stop_price,
+            OrderSide::Ask => last_price <= stop_price,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn place_stop_order(
+        owner: T::AccountId,
+        order_id: OrderId,
+        market: TradingPair,
+        side: OrderSide,
+        stop_price: T::Balance,
+        price: Option<T::Balance>,
+        quantity: T::Balance,
+        lot_size: T::Balance,
+    ) -> DispatchResult {
+        if let Some(last_price) = Self::last_traded_price(market) {
+            ensure!(
+                !Self::stop_reached(side, stop_price, last_price),
+                Error::<T>::StopPriceReached
+            );
+        }
+        // Bids of stop loss orders are valued at their stop price
+        let reserved = match side {
+            OrderSide::Bid => Self::notional(price.unwrap_or(stop_price), quantity, lot_size)?,
+            OrderSide::Ask => quantity,
+        };
+        T::Currency::reserve(Self::reserved_asset(market, side), &owner, reserved)?;
+        <StopQueue<T>>::try_mutate(market, side, |queue| -> DispatchResult {
+            ensure!(
+                queue.len() < T::MaxStopOrders::get() as usize,
+                Error::<T>::TooManyStopOrders
+            );
+            // Keep the queue ordered by how soon its orders trigger, in time priority
+            let index = queue
+                .iter()
+                .position(|(level, _)| match side {
+                    OrderSide::Bid => *level > stop_price,
+                    OrderSide::Ask => *level < stop_price,
+                })
+                .unwrap_or(queue.len());
+            queue.insert(index, (stop_price, order_id));
+            Ok(())
+        })?;
+        <StopOrders<T>>::insert(
+            order_id,
+            StopOrder {
+                owner: owner.clone(),
+                market,
+                side,
+                stop_price,
+                price,
+                quantity,
+                reserved,
+            },
+        );
+        Self::deposit_event(RawEvent::StopOrderPlaced(
+            order_id, owner, market, side, stop_price, quantity,
+        ));
+        Ok(())
+    }
+
+    /// Places the stop orders whose stop price was reached, up to `MaxStopTriggersPerBlock`
+    fn trigger_stop_orders() -> Weight {
+        let mut markets = PriceMovedMarkets::take();
+        let mut weight = T::DbWeight::get().reads_writes(1, 1);
+        let mut budget = T::MaxStopTriggersPerBlock::get();
+        let mut deferred = Vec::new();
+        for market in markets.drain(..) {
+            if budget == 0 {
+                deferred.push(market);
+                continue;
+            }
+            let last_price = match Self::last_traded_price(market) {
+                Some(price) => price,
+                None => continue,
+            };
+            weight = weight.saturating_add(T::DbWeight::get().reads(3));
+            for side in [OrderSide::Bid, OrderSide::Ask].iter() {
+                let mut queue = Self::stop_queue(market, side);
+                let mut triggered = 0;
+                while budget > 0 {
+                    match queue.get(triggered) {
+                        Some((stop_price, _))
+                            if Self::stop_reached(*side, *stop_price, last_price) => {}
+                        _ => break,
+                    }
+                    triggered += 1;
+                    budget -= 1;
+                }
+                if triggered == 0 {
+                    continue;
+                }
+                let order_ids: Vec<OrderId> =
+                    queue.drain(..triggered).map(|(_, order_id)| order_id).collect();
+                <StopQueue<T>>::insert(market, side, queue);
+                for order_id in order_ids {
+                    Self::trigger_stop_order(order_id);
+                    weight = weight.saturating_add(<T as Config>::WeightInfo::place_order(
+                        T::MaxMatchesPerOrder::get(),
+                    ));
+                }
+            }
+            if budget == 0 {
+                deferred.push(market);
+            }
+        }
+        if !deferred.is_empty() {
+            PriceMovedMarkets::mutate(|moved| {
+                for market in deferred {
+                    if !moved.contains(&market) {
+                        moved.push(market);
+                    }
+                }
+            });
+        }
+        weight
+    }
+
+    /// Places the TWAP slices due at `now`, at most `MaxTwapSlicesPerBlock` are scheduled
+    fn execute_twap_slices(now: T::BlockNumber) -> Weight {
+        let due = <TwapSchedule<T>>::take(now);
+        let mut weight = T::DbWeight::get().reads_writes(1, 1);
+        // Rescheduling looks at the schedule of up to `MinTwapInterval` blocks
+        let search: Weight = T::MinTwapInterval::get().unique_saturated_into();
+        for order_id in due {
+            Self::execute_twap_slice(order_id, now);
+            weight = weight
+                .saturating_add(<T as Config>::WeightInfo::place_order(T::MaxMatchesPerOrder::get()))
+                .saturating_add(T::DbWeight::get().reads_writes(search.saturating_add(2), 3));
+        }
+        weight
+    }
+
+    /// Adds `order_id` to the schedule of the first block from `at` with room for another slice
+    ///
+    /// Looks `MinTwapInterval` blocks ahead, so a moved slice still comes before the next one
+    /// of the same order.
+    fn schedule_twap_slice(order_id: OrderId, at: T::BlockNumber) -> Option<T::BlockNumber> {
+        let cap = T::MaxTwapSlicesPerBlock::get() as usize;
+        let end = at.saturating_add(T::MinTwapInterval::get().max(One::one()));
+        let mut block = at;
+        while block < end {
+            if <TwapSchedule<T>>::decode_len(block).unwrap_or(0) < cap {
+                <TwapSchedule<T>>::append(block, order_id);
+                return Some(block);
+            }
+            block = block.saturating_add(One::one());
+        }
+        None
+    }
+
+    /// Places the next slice of a TWAP order as a market order and schedules the one after
+    fn execute_twap_slice(order_id: OrderId, now: T::BlockNumber) {
+        let mut twap = match Self::twap_order(order_id) {
+            Some(twap) => twap,
+            None => return,
+        };
+        let slice = twap.executed_slices;
+        let last_slice = slice + 1 == twap.slices;
+        let quantity = if last_slice {
+            twap.quantity
+                .saturating_sub(twap.slice_quantity.saturating_mul((twap.slices - 1).into()))
+        } else {
+            twap.slice_quantity
+        };
+        // The funds of the slice are released outside the transaction, so they are free again
+        // if it fails
+        let released = match (last_slice, twap.side, twap.limit) {
+            (true, _, _) => twap.reserved,
+            (false, OrderSide::Bid, Some(price)) => {
+                Self::notional(price, quantity, twap.lot_size).unwrap_or(twap.reserved)
+            }
+            (false, _, _) => quantity,
+        }
+        .min(twap.reserved);
+        T::Currency::unreserve(Self::reserved_asset(twap.market, twap.side), &twap.owner, released);
+        twap.reserved = twap.reserved.saturating_sub(released);
+        let child_id = Self::next_order_id();
+        NextOrderId::put(child_id.saturating_add(1));
+        let result = with_transaction(|| {
+            let result = Self::open_market(&twap.market)
+                .map_err(DispatchError::from)
+                .and_then(|_| {
+                    Self::match_order(
+                        &twap.owner,
+                        child_id,
+                        twap.market,
+                        twap.side,
+                        twap.limit,
+                        quantity,
+                    )
+                });
+            match result {
+                Ok(remaining) => TransactionOutcome::Commit(Ok(remaining)),
+                Err(error) => TransactionOutcome::Rollback(Err(error)),
+            }
+        });
+        match result {
+            Ok(remaining) => {
+                let filled = quantity.saturating_sub(remaining);
+                twap.filled = twap.filled.saturating_add(filled);
+                Self::deposit_event(RawEvent::TwapSliceExecuted(
+                    order_id,
+                    slice,
+                    child_id,
+                    filled,
+                    twap.filled,
+                ));
+            }
+            Err(error) => Self::deposit_event(RawEvent::TwapSliceFailed(order_id, slice, error)),
+        }
+        twap.executed_slices += 1;
+        if twap.executed_slices >= twap.slices {
+            <TwapOrders<T>>::remove(order_id);
+            Self::deposit_event(RawEvent::TwapOrderCompleted(
+                order_id,
+                twap.owner,
+                twap.filled,
+            ));
+        } else {
+            match Self::schedule_twap_slice(order_id, now.saturating_add(twap.interval)) {
+                Some(next_slice_at) => {
+                    twap.next_slice_at = next_slice_at;
+                    <TwapOrders<T>>::insert(order_id, twap);
+                }
+                None => {
+                    T::Currency::unreserve(
+                        Self::reserved_asset(twap.market, twap.side),
+                        &twap.owner,
+                        twap.reserved,
+                    );
+                    <TwapOrders<T>>::remove(order_id);
+                    Self::deposit_event(RawEvent::TwapOrderStopped(
+                        order_id,
+                        twap.owner,
+                        twap.filled,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Places a triggered stop order, its trades are rolled back if it fails
+    fn trigger_stop_order(order_id: OrderId) {
+        let order = match <StopOrders<T>>::take(order_id) {
+            Some(order) => order,
+            None => return,
+        };
+        // Released outside the transaction, so the funds are free again if placing fails
+        T::Currency::unreserve(
+            Self::reserved_asset(order.market, order.side),
+            &order.owner,
+            order.reserved,
+        );
+        let result = with_transaction(|| {
+            let result = Self::open_market(&order.market)
+                .map_err(DispatchError::from)
+                .and_then(|info| {
+                    let time_in_force = match order.price {
+                        Some(_) => TimeInForce::GoodTillCancelled,
+                        None => TimeInForce::ImmediateOrCancel,
+                    };
+                    Self::execute_order(
+                        &order.owner,
+                        order_id,
+                        order.market,
+                        order.side,
+                        order.price,
+                        time_in_force,
+                        order.quantity,
+                        &info,
+                    )
+                });
+            match result {
+                Ok(()) => TransactionOutcome::Commit(Ok(())),
+                Err(error) => TransactionOutcome::Rollback(Err(error)),
+            }
+        });
+        match result {
+            Ok(()) => Self::deposit_event(RawEvent::StopOrderTriggered(order_id, order.owner)),
+            Err(error) => {
+                Self::deposit_event(RawEvent::StopOrderFailed(order_id, order.owner, error))
+            }
+        }
+    }
+
+    /// Value of `quantity` at `price` per lot of `lot_size` in quote asset
+    fn notional(
+        price: T::Balance,
+        quantity: T::Balance,
+        lot_size: T::Balance,
+    ) -> Result<T::Balance, Error<T>> {
+        price
+            .checked_mul(&(quantity / lot_size))
+            .ok_or(Error::<T>::Overflow)
+    }
+
+    /// Asset reserved by resting orders on `side` of `market`
+    fn reserved_asset(market: TradingPair, side: OrderSide) -> AssetId {
+        match side {
+            OrderSide::Bid => market.quote,
+            OrderSide::Ask => market.base,
+        }
+    }
+
+    /// Whether an incoming order with `limit` trades against a resting order at `price`
+    fn crosses(side: OrderSide, limit: Option<T::Balance>, price: T::Balance) -> bool {
+        match (side, limit) {
+            (_, None) => true,
+            (OrderSide::Bid, Some(limit)) => price <= limit,
+            (OrderSide::Ask, Some(limit)) => price >= limit,
+        }
+    }
+
+    /// Whether an order on `side` with `limit` trades against the best opposite price level
+    fn crosses_book(market: TradingPair, side: OrderSide, limit: Option<T::Balance>) -> bool {
+        Self::price_levels(market, side.opposite())
+            .first()
+            .map_or(false, |best| Self::crosses(side, limit, *best))
+    }
+
+    /// Matches an incoming order against the opposite side of the book
+    ///
+    /// Returns the quantity left unfilled.
+    fn match_order(
+        taker: &T::AccountId,
+        taker_order_id: OrderId,
+        market: TradingPair,
+        side: OrderSide,
+        limit: Option<T::Balance>,
+        quantity: T::Balance,
+    ) -> Result<T::Balance, DispatchError> {
+        let maker_side = side.opposite();
+        let mut levels = Self::price_levels(market, maker_side);
+        let mut remaining = quantity;
+        let mut matches = 0u32;
+        let mut last_price = None;
+        while !remaining.is_zero() && matches < T::MaxMatchesPerOrder::get() {
+            let price = match levels.first() {
+                Some(price) if Self::crosses(side, limit, *price) => *price,
+                _ => break,
+            };
+            let mut queue = Self::level_orders((market, maker_side), price);
+            while !remaining.is_zero() && matches < T::MaxMatchesPerOrder::get() {
+                let maker_order_id = match queue.first() {
+                    Some(order_id) => *order_id,
+                    None => break,
+                };
+                let mut maker_order =
+                    Self::order(maker_order_id).ok_or(Error::<T>::OrderNotFound)?;
+                let fill = remaining.min(maker_order.remaining);
+                let paid = Self::settle_fill(&market, side, taker, &maker_order, price, fill)?;
+                remaining -= fill;
+                maker_order.remaining -= fill;
+                maker_order.reserved = maker_order.reserved.saturating_sub(paid);
+                matches += 1;
+                last_price = Some(price);
+                Self::deposit_event(RawEvent::OrderFilled(
+                    market,
+                    maker_order_id,
+                    maker_order.owner.clone(),
+                    taker_order_id,
+                    taker.clone(),
+                    price,
+                    fill,
+                ));
+                if maker_order.remaining.is_zero() {
+                    // Fills of partial lots leave a rounding rest in the reserve
+                    T::Currency::unreserve(
+                        Self::reserved_asset(market, maker_side),
+                        &maker_order.owner,
+                        maker_order.reserved,
+                    );
+                    queue.remove(0);
+                    <Orders<T>>::remove(maker_order_id);
+                } else {
+                    <Orders<T>>::insert(maker_order_id, maker_order);
+                }
+            }
+            if queue.is_empty() {
+                <LevelOrders<T>>::remove((market, maker_side), price);
+                levels.remove(0);
+            } else {
+                <LevelOrders<T>>::insert((market, maker_side), price, queue);
+            }
+        }
+        <PriceLevels<T>>::insert(market, maker_side, levels);
+        if let Some(price) = last_price {
+            <LastTradedPrice<T>>::insert(market, price);
+            PriceMovedMarkets::mutate(|moved| {
+                if !moved.contains(&market) {
+                    moved.push(market);
+                }
+            });
+        }
+        Ok(remaining)
+    }
+
+    /// Exchanges `quantity` of base asset at `price` between the taker and a resting order
+    ///
+    /// The trade is valued with the lot size of the resting order. Returns the amount taken
+    /// from its reserve.
+    fn settle_fill(
+        market: &TradingPair,
+        taker_side: OrderSide,
+        taker: &T::AccountId,
+        maker_order: &OrderOf<T>,
+        price: T::Balance,
+        quantity: T::Balance,
+    ) -> Result<T::Balance, DispatchError> {
+        let maker = &maker_order.owner;
+        let value = Self::notional(price, quantity, maker_order.lot_size)?;
+        let (maker_asset, maker_amount, taker_asset, taker_amount) = match taker_side {
+            OrderSide::Bid => (market.base, quantity, market.quote, value),
+            OrderSide::Ask => (market.quote, value, market.base, quantity),
+        };
+        let unpaid = T::Currency::repatriate_reserved(
+            maker_asset,
+            maker,
+            taker,
+            maker_amount,
+            BalanceStatus::Free,
+        )?;
+        ensure!(unpaid.is_zero(), Error::<T>::InsufficientReserve);
+        T::Currency::transfer(taker_asset, taker, maker, taker_amount)?;
+        Ok(maker_amount)
+    }
+
+    /// Reserves the funds of the unfilled quantity and adds it to the book
+    fn rest_order(
+        order_id: OrderId,
+        owner: T::AccountId,
+        market: TradingPair,
+        side: OrderSide,
+        price: T::Balance,
+        remaining: T::Balance,
+        info: &MarketInfo<T::Balance>,
+    ) -> DispatchResult {
+        let reserved = match side {
+            OrderSide::Bid => Self::notional(price, remaining, info.lot_size)?,
+            OrderSide::Ask => remaining,
+        };
+        T::Currency::reserve(Self::reserved_asset(market, side), &owner, reserved)?;
+        let order = Order {
+            owner: owner.clone(),
+            market,
+            side,
+            price,
+            remaining,
+            lot_size: info.lot_size,
+            reserved,
+            placed_at: <system::Module<T>>::block_number(),
+        };
+
+        let mut levels = Self::price_levels(market, side);
+        let position = levels.iter().position(|level| match side {
+            OrderSide::Bid => *level <= price,
+            OrderSide::Ask => *level >= price,
+        });
+        match position {
+            Some(index) if levels[index] == price => {}
+            _ => {
+                ensure!(
+                    levels.len() < T::MaxPriceLevels::get() as usize,
+                    Error::<T>::TooManyPriceLevels
+                );
+                let index = position.unwrap_or(levels.len());
+                levels.insert(index, price);
+                <PriceLevels<T>>::insert(market, side, levels);
+            }
+        }
+        <LevelOrders<T>>::try_mutate((market, side), price, |queue| -> DispatchResult {
+            ensure!(
+                queue.len() < T::MaxOrdersPerLevel::get() as usize,
+                Error::<T>::TooManyOrdersAtLevel
+            );
+            queue.push(order_id);
+            Ok(())
+        })?;
+        <Orders<T>>::insert(order_id, order);
+        Self::deposit_event(RawEvent::OrderPlaced(
+            order_id, owner, market, side, price, remaining,
+        ));
+        Ok(())
+    }
+
+    /// Removes a resting order from its price level
+    fn remove_from_level(order_id: OrderId, order: &OrderOf<T>) {
+        let key = (order.market, order.side);
+        let mut queue = Self::level_orders(key, order.price);
+        queue.retain(|id| *id != order_id);
+        if queue.is_empty() {
+            <LevelOrders<T>>::remove(key, order.price);
+            <PriceLevels<T>>::mutate(order.market, order.side, |levels| {
+                levels.retain(|level| *level != order.price)
+            });
+        } else {
+            <LevelOrders<T>>::insert(key, order.price, queue);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+
+use crate as polkadex_orderbook;
+use frame_support::{assert_ok, parameter_types};
+use orml_traits::parameter_type_with_key;
+use polkadex_primitives::assets::AssetId;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Storage, Event<T>},
+        Orderbook: polkadex_orderbook::{Pallet, Call, Storage, Event<T>},
+        OrmlToken: orml_tokens::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+pub type Balance = u128;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type DbWeight = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+parameter_types! {
+    pub TreasuryModuleAccount: u64 = 100;
+}
+
+parameter_type_with_key! {
+    pub ExistentialDeposits: |_currency_id: AssetId| -> Balance {
+        Zero::zero()
+    };
+}
+
+impl orml_tokens::Config for Test {
+    type Event = ();
+    type Balance = Balance;
+    type Amount = i128;
+    type CurrencyId = AssetId;
+    type WeightInfo = ();
+    type ExistentialDeposits = ExistentialDeposits;
+    type OnDust = orml_tokens::TransferDust<Test, TreasuryModuleAccount>;
+}
+
+/// Open market trading POLKADEX against DOT
+pub const OPEN_MARKET: TradingPair = TradingPair {
+    base: AssetId::POLKADEX,
+    quote: AssetId::DOT,
+};
+
+/// Halted market trading DOT against POLKADEX
+pub const HALTED_MARKET: TradingPair = TradingPair {
+    base: AssetId::DOT,
+    quote: AssetId::POLKADEX,
+};
+
+thread_local! {
+    static LOT_SIZE: RefCell<Balance> = RefCell::new(100);
+}
+
+/// Changes the lot size of the test markets
+pub fn set_lot_size(lot_size: Balance) {
+    LOT_SIZE.with(|lot| *lot.borrow_mut() = lot_size);
+}
+
+pub struct TestMarkets;
+
+impl MarketRegistry<Balance> for TestMarkets {
+    fn market_info(market: &TradingPair) -> Option<MarketInfo<Balance>> {
+        let status = if *market == OPEN_MARKET {
+            MarketStatus::Open
+        } else if *market == HALTED_MARKET {
+            MarketStatus::Halted
+        } else {
+            return None;
+        };
+        Some(MarketInfo {
+            tick_size: 10,
+            lot_size: LOT_SIZE.with(|lot| *lot.borrow()),
+            min_notional: 50,
+            status,
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_market(_market: TradingPair, _info: MarketInfo<Balance>) {}
+}
+
+parameter_types! {
+    pub const MaxMatchesPerOrder: u32 = 10;
+    pub const MaxPriceLevels: u32 = 10;
+    pub const MaxOrdersPerLevel: u32 = 10;
+    pub const MaxStopOrders: u32 = 10;
+    pub const MaxStopTriggersPerBlock: u32 = 2;
+    pub const MinTwapInterval: u64 = 2;
+    pub const MaxTwapDuration: u64 = 100;
+    pub const MaxTwapSlicesPerBlock: u32 = 1;
+}
+
+impl Config for Test {
+    type Event = ();
+    type Currency = OrmlToken;
+    type Markets = TestMarkets;
+    type MaxMatchesPerOrder = MaxMatchesPerOrder;
+    type MaxPriceLevels = MaxPriceLevels;
+    type MaxOrdersPerLevel = MaxOrdersPerLevel;
+    type MaxStopOrders = MaxStopOrders;
+    type MaxStopTriggersPerBlock = MaxStopTriggersPerBlock;
+    type MinTwapInterval = MinTwapInterval;
+    type MaxTwapDuration = MaxTwapDuration;
+    type MaxTwapSlicesPerBlock = MaxTwapSlicesPerBlock;
+    type WeightInfo = ();
+}
+
+pub type PolkadexOrderbook = Pallet<Test>;
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+
+pub fn new_tester() -> sp_io::TestExternalities {
+    let storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        for account in [ALICE, BOB].iter() {
+            for asset_id in [AssetId::POLKADEX, AssetId::DOT].iter() {
+                assert_ok!(OrmlToken::deposit(*asset_id, account, 10_000));
+            }
+        }
+    });
+    ext
+}
@@ -0,0 +1,588 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+
+use super::*;
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
+use polkadex_primitives::assets::AssetId;
+use polkadex_primitives::orders::TimeInForce;
+
+fn limit(price: Balance) -> OrderType<Balance> {
+    OrderType::Limit {
+        price,
+        time_in_force: TimeInForce::GoodTillCancelled,
+    }
+}
+
+#[test]
+fn test_limit_order_rests_on_book() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            300
+        ));
+        // 3 lots at 20 DOT per lot
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 60);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_940);
+        assert_eq!(PolkadexOrderbook::order(0).unwrap().remaining, 300);
+        assert_eq!(
+            PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Bid),
+            vec![20]
+        );
+        assert_eq!(
+            PolkadexOrderbook::level_orders((OPEN_MARKET, OrderSide::Bid), 20),
+            vec![0]
+        );
+        assert_eq!(PolkadexOrderbook::next_order_id(), 1);
+    });
+}
+
+#[test]
+fn test_matching_order_fills_resting_order() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            300
+        ));
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(20),
+            300
+        ));
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &ALICE), 10_300);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_940);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 9_700);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 10_060);
+        assert_eq!(PolkadexOrderbook::order(0), None);
+        assert_eq!(PolkadexOrderbook::order(1), None);
+        assert!(PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Bid).is_empty());
+        assert!(PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Ask).is_empty());
+    });
+}
+
+#[test]
+fn test_partial_fill_at_maker_price() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            300
+        ));
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(10),
+            100
+        ));
+        assert_eq!(PolkadexOrderbook::order(0).unwrap().remaining, 200);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 40);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 10_020);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &ALICE), 10_100);
+    });
+}
+
+#[test]
+fn test_best_price_is_matched_first() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            100
+        ));
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(30),
+            100
+        ));
+        assert_eq!(
+            PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Bid),
+            vec![30, 20]
+        );
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(20),
+            100
+        ));
+        assert_eq!(PolkadexOrderbook::order(1), None);
+        assert_eq!(PolkadexOrderbook::order(0).unwrap().remaining, 100);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 10_030);
+    });
+}
+
+#[test]
+fn test_market_order_drops_unfilled_quantity() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(30),
+            200
+        ));
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            OrderType::Market,
+            500
+        ));
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_200);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 9_940);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &BOB), 0);
+        assert_eq!(PolkadexOrderbook::order(1), None);
+        assert!(PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Bid).is_empty());
+    });
+}
+
+#[test]
+fn test_cancel_order_unreserves_funds() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(20),
+            300
+        ));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 300);
+        assert_noop!(
+            PolkadexOrderbook::cancel_order(Origin::signed(BOB), 0),
+            Error::<Test>::NotTheOwner
+        );
+        assert_ok!(PolkadexOrderbook::cancel_order(Origin::signed(ALICE), 0));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &ALICE), 10_000);
+        assert_eq!(PolkadexOrderbook::order(0), None);
+        assert!(PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Ask).is_empty());
+        assert_noop!(
+            PolkadexOrderbook::cancel_order(Origin::signed(ALICE), 0),
+            Error::<Test>::OrderNotFound
+        );
+    });
+}
+
+#[test]
+fn test_order_validation() {
+    new_tester().execute_with(|| {
+        assert_noop!(
+            PolkadexOrderbook::place_order(Origin::signed(ALICE), OPEN_MARKET, OrderSide::Bid, limit(15), 100),
+            Error::<Test>::InvalidPrice
+        );
+        assert_noop!(
+            PolkadexOrderbook::place_order(Origin::signed(ALICE), OPEN_MARKET, OrderSide::Bid, limit(20), 150),
+            Error::<Test>::InvalidQuantity
+        );
+        assert_noop!(
+            PolkadexOrderbook::place_order(Origin::signed(ALICE), OPEN_MARKET, OrderSide::Bid, limit(10), 100),
+            Error::<Test>::BelowMinNotional
+        );
+        assert_noop!(
+            PolkadexOrderbook::place_order(Origin::signed(ALICE), HALTED_MARKET, OrderSide::Bid, limit(20), 100),
+            Error::<Test>::MarketNotOpen
+        );
+        let unknown = TradingPair {
+            base: AssetId::DOT,
+            quote: AssetId::DOT,
+        };
+        assert_noop!(
+            PolkadexOrderbook::place_order(Origin::signed(ALICE), unknown, OrderSide::Bid, limit(20), 100),
+            Error::<Test>::MarketNotFound
+        );
+    });
+}
+
+#[test]
+fn test_post_only_order_rejected_when_crossing() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(30),
+            100
+        ));
+        let post_only = |price| OrderType::Limit {
+            price,
+            time_in_force: TimeInForce::PostOnly,
+        };
+        assert_noop!(
+            PolkadexOrderbook::place_order(Origin::signed(BOB), OPEN_MARKET, OrderSide::Bid, post_only(30), 100),
+            Error::<Test>::PostOnlyWouldCross
+        );
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            post_only(20),
+            100
+        ));
+        assert_eq!(PolkadexOrderbook::order(1).unwrap().price, 20);
+    });
+}
+
+#[test]
+fn test_fill_or_kill_and_immediate_or_cancel() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(30),
+            100
+        ));
+        assert_noop!(
+            PolkadexOrderbook::place_order(
+                Origin::signed(BOB),
+                OPEN_MARKET,
+                OrderSide::Bid,
+                OrderType::Limit {
+                    price: 30,
+                    time_in_force: TimeInForce::FillOrKill
+                },
+                200
+            ),
+            Error::<Test>::FillOrKillNotFilled
+        );
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            OrderType::Limit {
+                price: 30,
+                time_in_force: TimeInForce::ImmediateOrCancel
+            },
+            200
+        ));
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_100);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &BOB), 0);
+        assert_eq!(PolkadexOrderbook::order(1), None);
+        assert!(PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Bid).is_empty());
+    });
+}
+
+#[test]
+fn test_stop_order_triggered_by_last_traded_price() {
+    new_tester().execute_with(|| {
+        // Trade at 30 sets the last traded price
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(30),
+            100
+        ));
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(30),
+            100
+        ));
+        assert_eq!(PolkadexOrderbook::last_traded_price(OPEN_MARKET), Some(30));
+        assert_noop!(
+            PolkadexOrderbook::place_order(
+                Origin::signed(ALICE),
+                OPEN_MARKET,
+                OrderSide::Ask,
+                OrderType::StopLoss { stop_price: 30 },
+                100
+            ),
+            Error::<Test>::StopPriceReached
+        );
+        // Sell if the price falls to 20
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            OrderType::StopLoss { stop_price: 20 },
+            100
+        ));
+        assert_eq!(PolkadexOrderbook::stop_queue(OPEN_MARKET, OrderSide::Ask), vec![(20, 2)]);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 100);
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            200
+        ));
+        PolkadexOrderbook::on_initialize(2);
+        assert!(PolkadexOrderbook::stop_order(2).is_some());
+        assert_eq!(PolkadexOrderbook::order(3).unwrap().remaining, 200);
+
+        // Trade at 20 triggers the stop order against the remaining bid
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(20),
+            100
+        ));
+        assert_eq!(PolkadexOrderbook::last_traded_price(OPEN_MARKET), Some(20));
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_200);
+        PolkadexOrderbook::on_initialize(3);
+        assert_eq!(PolkadexOrderbook::stop_order(2), None);
+        assert!(PolkadexOrderbook::stop_queue(OPEN_MARKET, OrderSide::Ask).is_empty());
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_300);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::POLKADEX, &ALICE), 0);
+        assert_eq!(PolkadexOrderbook::order(3), None);
+    });
+}
+
+#[test]
+fn test_stop_orders_reserve_funds_while_waiting() {
+    new_tester().execute_with(|| {
+        // Stop loss bids are valued at the stop price, 100 lots at 110 DOT
+        assert_noop!(
+            PolkadexOrderbook::place_order(
+                Origin::signed(ALICE),
+                OPEN_MARKET,
+                OrderSide::Bid,
+                OrderType::StopLoss { stop_price: 110 },
+                10_000
+            ),
+            orml_tokens::Error::<Test>::BalanceTooLow
+        );
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            OrderType::StopLimit { stop_price: 40, price: 50 },
+            300
+        ));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 150);
+        assert_ok!(PolkadexOrderbook::cancel_order(Origin::signed(ALICE), 0));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 10_000);
+    });
+}
+
+#[test]
+fn test_twap_order_executes_in_slices() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(20),
+            1_000
+        ));
+        assert_noop!(
+            PolkadexOrderbook::place_twap_order(Origin::signed(BOB), OPEN_MARKET, OrderSide::Bid, 500, Some(20), 3, 1),
+            Error::<Test>::TwapIntervalTooShort
+        );
+        assert_noop!(
+            PolkadexOrderbook::place_twap_order(Origin::signed(BOB), OPEN_MARKET, OrderSide::Bid, 500, Some(20), 6, 2),
+            Error::<Test>::InvalidTwapSlices
+        );
+        assert_noop!(
+            PolkadexOrderbook::place_twap_order(Origin::signed(BOB), OPEN_MARKET, OrderSide::Bid, 500, Some(20), 5, 30),
+            Error::<Test>::TwapDurationTooLong
+        );
+        assert_noop!(
+            PolkadexOrderbook::place_twap_order(Origin::signed(BOB), OPEN_MARKET, OrderSide::Bid, 500, None, 3, 2),
+            Error::<Test>::TwapBidWithoutLimit
+        );
+        // 2 lots, 2 lots and 1 lot every 2 blocks
+        assert_ok!(PolkadexOrderbook::place_twap_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            500,
+            Some(20),
+            3,
+            2
+        ));
+        assert_eq!(PolkadexOrderbook::twap_order(1).unwrap().slice_quantity, 200);
+        assert_eq!(PolkadexOrderbook::twap_schedule(2), vec![1]);
+        // 5 lots at the limit price of 20 DOT
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &BOB), 100);
+
+        PolkadexOrderbook::on_initialize(2);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_200);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &BOB), 60);
+        assert_eq!(PolkadexOrderbook::twap_schedule(4), vec![1]);
+        PolkadexOrderbook::on_initialize(4);
+        assert_eq!(PolkadexOrderbook::twap_order(1).unwrap().filled, 400);
+        PolkadexOrderbook::on_initialize(6);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_500);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 9_900);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &BOB), 0);
+        assert_eq!(PolkadexOrderbook::twap_order(1), None);
+        assert_eq!(PolkadexOrderbook::order(0).unwrap().remaining, 500);
+    });
+}
+
+#[test]
+fn test_twap_slices_capped_per_block_and_cancelled() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            limit(20),
+            1_000
+        ));
+        assert_ok!(PolkadexOrderbook::place_twap_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            200,
+            Some(20),
+            2,
+            10
+        ));
+        assert_ok!(PolkadexOrderbook::place_twap_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            1_000,
+            Some(10),
+            2,
+            10
+        ));
+        // One slice per block, the second order moves to the next block
+        assert_eq!(PolkadexOrderbook::twap_schedule(2), vec![1]);
+        assert_eq!(PolkadexOrderbook::twap_schedule(3), vec![2]);
+        assert_eq!(PolkadexOrderbook::twap_order(2).unwrap().next_slice_at, 3);
+        // No room left within `MinTwapInterval` blocks
+        assert_noop!(
+            PolkadexOrderbook::place_twap_order(Origin::signed(BOB), OPEN_MARKET, OrderSide::Bid, 200, Some(20), 2, 10),
+            Error::<Test>::TwapScheduleFull
+        );
+
+        PolkadexOrderbook::on_initialize(2);
+        assert_eq!(PolkadexOrderbook::twap_schedule(12), vec![1]);
+        // Limit price keeps the slice from trading
+        PolkadexOrderbook::on_initialize(3);
+        assert_eq!(PolkadexOrderbook::twap_order(2).unwrap().filled, 0);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 50);
+
+        assert_noop!(
+            PolkadexOrderbook::cancel_twap_order(Origin::signed(ALICE), 1),
+            Error::<Test>::NotTheOwner
+        );
+        assert_ok!(PolkadexOrderbook::cancel_twap_order(Origin::signed(BOB), 1));
+        // The cancelled order leaves the schedule and its funds are released
+        assert!(PolkadexOrderbook::twap_schedule(12).is_empty());
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &BOB), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 9_980);
+        PolkadexOrderbook::on_initialize(12);
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &BOB), 10_100);
+        assert_eq!(PolkadexOrderbook::twap_order(1), None);
+    });
+}
+
+#[test]
+fn test_cancel_after_lot_change_unreserves_placed_funds() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            300
+        ));
+        set_lot_size(200);
+        assert_ok!(PolkadexOrderbook::cancel_order(Origin::signed(ALICE), 0));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 10_000);
+    });
+}
+
+#[test]
+fn test_fill_after_lot_change_uses_order_lot() {
+    new_tester().execute_with(|| {
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(20),
+            300
+        ));
+        set_lot_size(200);
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(BOB),
+            OPEN_MARKET,
+            OrderSide::Ask,
+            OrderType::Market,
+            200
+        ));
+        // 2 lots of the resting order at 20 DOT per lot
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &BOB), 10_040);
+        let order = PolkadexOrderbook::order(0).unwrap();
+        assert_eq!((order.remaining, order.lot_size, order.reserved), (100, 100, 20));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 20);
+        assert_ok!(PolkadexOrderbook::cancel_order(Origin::signed(ALICE), 0));
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 0);
+        assert_eq!(OrmlToken::free_balance(AssetId::DOT, &ALICE), 9_960);
+    });
+}
+
+#[test]
+fn test_remainder_crossing_book_after_max_matches_expires() {
+    new_tester().execute_with(|| {
+        // One more crossing ask than an order is matched against
+        for index in 0..=MaxMatchesPerOrder::get() {
+            let price = if index < MaxOrdersPerLevel::get() { 20 } else { 30 };
+            assert_ok!(PolkadexOrderbook::place_order(
+                Origin::signed(BOB),
+                OPEN_MARKET,
+                OrderSide::Ask,
+                limit(price),
+                100
+            ));
+        }
+        let order_id = PolkadexOrderbook::next_order_id();
+        assert_ok!(PolkadexOrderbook::place_order(
+            Origin::signed(ALICE),
+            OPEN_MARKET,
+            OrderSide::Bid,
+            limit(30),
+            1_200
+        ));
+        assert_eq!(OrmlToken::free_balance(AssetId::POLKADEX, &ALICE), 11_000);
+        assert_eq!(OrmlToken::reserved_balance(AssetId::DOT, &ALICE), 0);
+        assert_eq!(PolkadexOrderbook::order(order_id), None);
+        assert!(PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Bid).is_empty());
+        assert_eq!(
+            PolkadexOrderbook::price_levels(OPEN_MARKET, OrderSide::Ask),
+            vec![30]
+        );
+    });
+}
@@ -0,0 +1,99 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Weights for polkadex_orderbook
+//!
+//! PLACEHOLDERS: these are hand-written estimates, not benchmark output. The constants are
+//! round guesses and the reads and writes are counted from the code, nothing was measured.
+//! Replace the whole file before production by running `scripts/benchmark.sh`, which runs
+//! the benchmarks in `benchmarking.rs` with:
+//! ./target/release/polkadex-node benchmark --chain=dev --steps=50 --repeat=20
+//! --pallet=polkadex_orderbook --extrinsic=* --execution=wasm --wasm-execution=compiled
+//! --heap-pages=4096 --output=./pallets/orderbook/src/weights.rs
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for polkadex_orderbook.
+pub trait WeightInfo {
+    fn place_order(m: u32, ) -> Weight;
+    fn cancel_order() -> Weight;
+    fn place_twap_order() -> Weight;
+    fn cancel_twap_order() -> Weight;
+}
+
+/// Placeholder weights for polkadex_orderbook, to be replaced by benchmarked ones.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn place_order(m: u32, ) -> Weight {
+        (85_000_000 as Weight)
+            .saturating_add((62_000_000 as Weight).saturating_mul(m as Weight))
+            .saturating_add(T::DbWeight::get().reads(8 as Weight))
+            .saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(m as Weight)))
+            .saturating_add(T::DbWeight::get().writes(8 as Weight))
+            .saturating_add(T::DbWeight::get().writes((4 as Weight).saturating_mul(m as Weight)))
+    }
+    fn cancel_order() -> Weight {
+        (48_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn place_twap_order() -> Weight {
+        (52_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(13 as Weight))
+            .saturating_add(T::DbWeight::get().writes(5 as Weight))
+    }
+    fn cancel_twap_order() -> Weight {
+        (34_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn place_order(m: u32, ) -> Weight {
+        (85_000_000 as Weight)
+            .saturating_add((62_000_000 as Weight).saturating_mul(m as Weight))
+            .saturating_add(RocksDbWeight::get().reads(8 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((4 as Weight).saturating_mul(m as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(8 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((4 as Weight).saturating_mul(m as Weight)))
+    }
+    fn cancel_order() -> Weight {
+        (48_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(5 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(4 as Weight))
+    }
+    fn place_twap_order() -> Weight {
+        (52_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(13 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(5 as Weight))
+    }
+    fn cancel_twap_order() -> Weight {
+        (34_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+    }
+}
@@ -19,3 +19,6 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod assets;
+pub mod markets;
+pub mod ocex;
+pub mod orders;
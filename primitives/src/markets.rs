@@ -0,0 +1,67 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::RuntimeDebug;
+
+use crate::assets::AssetId;
+
+/// Market trading `base` against `quote`
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct TradingPair {
+    pub base: AssetId,
+    pub quote: AssetId,
+}
+
+/// Trading phase of a market
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum MarketStatus {
+    /// Registered, orders are not matched yet
+    PreOpen,
+    Open,
+    /// Trading temporarily suspended
+    Halted,
+    /// Permanently removed
+    Delisted,
+}
+
+/// Trading rules of a market
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct MarketInfo<Balance> {
+    /// Smallest price increment, in quote asset
+    pub tick_size: Balance,
+    /// Smallest quantity increment, in base asset
+    pub lot_size: Balance,
+    /// Smallest order value, in quote asset
+    pub min_notional: Balance,
+    pub status: MarketStatus,
+}
+
+/// Source of the trading rules of listed markets
+pub trait MarketRegistry<Balance> {
+    fn market_info(market: &TradingPair) -> Option<MarketInfo<Balance>>;
+
+    /// Lists `market` with the trading rules `info`
+    #[cfg(feature = "runtime-benchmarks")]
+    fn register_market(market: TradingPair, info: MarketInfo<Balance>);
+}
@@ -0,0 +1,88 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_arithmetic::FixedU128;
+use sp_core::RuntimeDebug;
+
+use crate::assets::AssetId;
+
+/// Fund movements which governance can pause
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum OcexOperation {
+    Deposit,
+    Withdraw,
+    Release,
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum WithdrawalStatus<BlockNumber> {
+    /// Waiting for the enclave
+    Pending,
+    /// Paid out by the enclave in the given block
+    Settled(BlockNumber),
+    /// Released by the enclave but held back by the outflow limit
+    Queued,
+    /// Refused by the enclave or governance in the given block
+    Rejected(BlockNumber),
+    /// Released by the enclave and paid out in the given block unless challenged
+    Delayed(BlockNumber),
+    /// Delayed release halted by a watcher until governance decides on it
+    Challenged,
+}
+
+/// Withdrawal requested by a user and settled by the enclave
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct WithdrawalRequest<AccountId, Balance, BlockNumber> {
+    pub account: AccountId,
+    pub asset_id: AssetId,
+    pub amount: Balance,
+    pub destination: AccountId,
+    /// Block in which the request was made
+    pub block: BlockNumber,
+    pub status: WithdrawalStatus<BlockNumber>,
+}
+
+/// Enclave registered to operate the exchange
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct EnclaveStatus<AccountId> {
+    pub account: AccountId,
+    pub mr_enclave: [u8; 32],
+    /// Whether the enclave may currently release funds
+    pub allowed: bool,
+    /// Whether the enclave sent a heartbeat recently
+    pub live: bool,
+}
+
+/// Custody reserves of an asset against the user balances reported by the enclave
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ReserveStatus<Balance> {
+    /// Balance of the custody account
+    pub reserves: Balance,
+    /// User balances reported by the enclave
+    pub liabilities: Balance,
+    /// Reserves per unit of liabilities, `None` without liabilities
+    pub ratio: Option<FixedU128>,
+}
@@ -0,0 +1,70 @@
+// This file is part of Polkadex.
+
+// Copyright (C) 2020-2021 Polkadex oü.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::RuntimeDebug;
+
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum OrderSide {
+    /// Buy base asset with quote asset
+    Bid,
+    /// Sell base asset for quote asset
+    Ask,
+}
+
+impl OrderSide {
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderSide::Bid => OrderSide::Ask,
+            OrderSide::Ask => OrderSide::Bid,
+        }
+    }
+}
+
+/// How long the unfilled quantity of a limit order stays on the book
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum TimeInForce {
+    /// Rests on the book until filled or cancelled
+    GoodTillCancelled,
+    /// Only rests on the book, rejected if it would trade on arrival
+    PostOnly,
+    /// Trades what it can on arrival, the remainder is cancelled
+    ImmediateOrCancel,
+    /// Trades its full quantity on arrival or is rejected
+    FillOrKill,
+}
+
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum OrderType<Balance> {
+    /// Trades at `price` or better
+    Limit {
+        price: Balance,
+        time_in_force: TimeInForce,
+    },
+    /// Trades at the best available prices, the remainder is cancelled
+    Market,
+    /// Limit order placed once the last traded price reaches `stop_price`
+    StopLimit { stop_price: Balance, price: Balance },
+    /// Market order placed once the last traded price reaches `stop_price`
+    StopLoss { stop_price: Balance },
+}
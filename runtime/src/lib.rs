@@ -15,6 +15,8 @@ use orml_traits::{parameter_type_with_key, MultiCurrencyExtended};
 use pallet_grandpa::fg_primitives;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use polkadex_primitives::assets::AssetId;
+use polkadex_primitives::markets::{MarketInfo, TradingPair};
+use polkadex_primitives::ocex::{EnclaveStatus, OcexOperation, ReserveStatus, WithdrawalRequest};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_runtime::traits::AccountIdConversion;
@@ -28,7 +30,7 @@ use sp_runtime::{
     transaction_validity::{TransactionSource, TransactionValidity},
     ApplyExtrinsicResult, MultiSignature,
 };
-use frame_support::{traits::InstanceFilter};
+use frame_support::traits::{Filter, InstanceFilter};
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
@@ -119,10 +121,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     //   `spec_version`, and `authoring_version` are the same between Wasm and native.
     // This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
     //   the compatible custom types.
-    spec_version: 100,
+    spec_version: 101,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -359,7 +361,7 @@ impl EnsureOrigin<Origin> for EnsureGovernance {
 
     #[cfg(feature = "runtime-benchmarks")]
     fn successful_origin() -> Origin {
-        Origin::from(RawOrigin::Signed(Default::default()))
+        Origin::from(RawOrigin::Signed(MODULE_ID.into_account()))
     }
 }
 
@@ -368,6 +370,7 @@ impl polkadex_fungible_assets::Config for Runtime {
 	type TreasuryAccountId = TreasuryAccountId;
 	type GovernanceOrigin = EnsureGovernance;
 	type NativeCurrency = BasicCurrencyAdapter<Runtime, Balances, Amount, BlockNumber>;
+	type WeightInfo = polkadex_fungible_assets::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
@@ -447,10 +450,105 @@ impl pallet_substratee_registry::Config for Runtime {
     type MomentsPerDay = MomentsPerDay;
 }
 
+parameter_types! {
+    pub const MaxReleaseBatch: u32 = 1000;
+    pub const WithdrawalRetention: BlockNumber = 7 * DAYS;
+    pub const ExodusTimeout: BlockNumber = 7 * DAYS;
+    pub const HeartbeatInterval: BlockNumber = 10 * MINUTES;
+    pub const QueuedReleaseDelay: BlockNumber = DAYS;
+    pub const OutflowBucketsPerPeriod: u32 = 24;
+    pub const ApprovalTimeout: BlockNumber = HOURS;
+    pub const HandoverTimeout: BlockNumber = 6 * HOURS;
+    pub const MaxFillsPerBatch: u32 = 1000;
+    pub const MaxTradingKeys: u32 = 10;
+    pub const MinTraderStake: Balance = 1_000_000_000_000_000;
+    pub const StakePerFeelessTransaction: Balance = 1_000_000_000_000;
+    pub const FeelessWindow: BlockNumber = HOURS;
+    pub const MaxFeelessPerBlock: u32 = 10;
+    pub const TraderUnbondingPeriod: BlockNumber = DAYS;
+    pub const ChallengeBond: Balance = 1_000_000_000_000_000;
+    pub const MaxDelayedReleasesPerBlock: u32 = 50;
+    pub const MaxLiabilityAssets: u32 = 100;
+}
+
+/// Order book and OCEX calls registered traders send without fees
+pub struct FeelessTradingCalls;
+impl Filter<Call> for FeelessTradingCalls {
+    fn filter(call: &Call) -> bool {
+        matches!(
+            call,
+            Call::PolkadexOrderbook(polkadex_orderbook::Call::place_order(..))
+                | Call::PolkadexOrderbook(polkadex_orderbook::Call::cancel_order(..))
+                | Call::PolkadexOrderbook(polkadex_orderbook::Call::place_twap_order(..))
+                | Call::PolkadexOrderbook(polkadex_orderbook::Call::cancel_twap_order(..))
+                | Call::PolkadexOcex(polkadex_ocex::Call::deposit(..))
+                | Call::PolkadexOcex(polkadex_ocex::Call::withdraw(..))
+                | Call::PolkadexOcex(polkadex_ocex::Call::register_trading_key(..))
+                | Call::PolkadexOcex(polkadex_ocex::Call::revoke_trading_key(..))
+        )
+    }
+}
+
+/// Enclaves OCEX trusts, keys added through `add_static_enclave` on dev builds without SGX
+#[cfg(feature = "dev-enclaves")]
+pub type OcexEnclaves = polkadex_ocex::StaticEnclaves<Runtime>;
+/// Enclaves OCEX trusts, those attested through the SubstraTEE registry
+#[cfg(not(feature = "dev-enclaves"))]
+pub type OcexEnclaves = polkadex_ocex::RegistryEnclaves<Runtime>;
+
 impl polkadex_ocex::Config for Runtime {
     type Event = Event;
     type OcexId = OcexModuleId;
     type Currency = Currencies;
+    type GovernanceOrigin = EnsureGovernance;
+    type EnclaveAuthority = OcexEnclaves;
+    type MaxReleaseBatch = MaxReleaseBatch;
+    type WithdrawalRetention = WithdrawalRetention;
+    type ExodusTimeout = ExodusTimeout;
+    type HeartbeatInterval = HeartbeatInterval;
+    type QueuedReleaseDelay = QueuedReleaseDelay;
+    type OutflowBucketsPerPeriod = OutflowBucketsPerPeriod;
+    type ApprovalTimeout = ApprovalTimeout;
+    type HandoverTimeout = HandoverTimeout;
+    type MaxFillsPerBatch = MaxFillsPerBatch;
+    type TradingKey = <Signature as Verify>::Signer;
+    type MaxTradingKeys = MaxTradingKeys;
+    type FeelessCalls = FeelessTradingCalls;
+    type MinTraderStake = MinTraderStake;
+    type StakePerFeelessTransaction = StakePerFeelessTransaction;
+    type FeelessWindow = FeelessWindow;
+    type MaxFeelessPerBlock = MaxFeelessPerBlock;
+    type TraderUnbondingPeriod = TraderUnbondingPeriod;
+    type ChallengeBond = ChallengeBond;
+    type MaxDelayedReleasesPerBlock = MaxDelayedReleasesPerBlock;
+    type MaxLiabilityAssets = MaxLiabilityAssets;
+    type WeightInfo = polkadex_ocex::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const MaxMatchesPerOrder: u32 = 50;
+    pub const MaxPriceLevels: u32 = 500;
+    pub const MaxOrdersPerLevel: u32 = 200;
+    pub const MaxStopOrders: u32 = 500;
+    pub const MaxStopTriggersPerBlock: u32 = 20;
+    pub const MinTwapInterval: BlockNumber = MINUTES;
+    pub const MaxTwapDuration: BlockNumber = 24 * HOURS;
+    pub const MaxTwapSlicesPerBlock: u32 = 20;
+}
+
+impl polkadex_orderbook::Config for Runtime {
+    type Event = Event;
+    type Currency = Currencies;
+    type Markets = PolkadexOcex;
+    type MaxMatchesPerOrder = MaxMatchesPerOrder;
+    type MaxPriceLevels = MaxPriceLevels;
+    type MaxOrdersPerLevel = MaxOrdersPerLevel;
+    type MaxStopOrders = MaxStopOrders;
+    type MaxStopTriggersPerBlock = MaxStopTriggersPerBlock;
+    type MinTwapInterval = MinTwapInterval;
+    type MaxTwapDuration = MaxTwapDuration;
+    type MaxTwapSlicesPerBlock = MaxTwapSlicesPerBlock;
+    type WeightInfo = polkadex_orderbook::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -474,7 +572,8 @@ construct_runtime!(
         Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>},
         PolkadexFungibleAsset: polkadex_fungible_assets::{Pallet, Call, Storage, Event<T>},
         SubstrateeRegistry: pallet_substratee_registry::{Pallet, Call, Storage, Event<T>},
-        PolkadexOcex: polkadex_ocex::{Pallet, Call, Event<T>},
+        PolkadexOcex: polkadex_ocex::{Pallet, Call, Storage, Event<T>},
+        PolkadexOrderbook: polkadex_orderbook::{Pallet, Call, Storage, Event<T>},
     }
 );
 
@@ -496,7 +595,7 @@ pub type SignedExtra = (
     frame_system::CheckEra<Runtime>,
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
-    pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    polkadex_ocex::FeelessTransactionPayment<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -641,6 +740,56 @@ impl_runtime_apis! {
         }
     }
 
+    impl polkadex_ocex_runtime_api::OcexApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime {
+        fn balance_snapshot_root(epoch: u64) -> Option<Hash> {
+            PolkadexOcex::balance_snapshot(epoch)
+        }
+
+        fn verify_balance_proof(
+            epoch: u64,
+            account: AccountId,
+            asset_id: AssetId,
+            balance: Balance,
+            proof: Vec<Hash>,
+        ) -> bool {
+            PolkadexOcex::verify_balance_proof(epoch, &account, asset_id, balance, &proof)
+        }
+
+        fn market(market: TradingPair) -> Option<MarketInfo<Balance>> {
+            PolkadexOcex::market(market)
+        }
+
+        fn markets() -> Vec<(TradingPair, MarketInfo<Balance>)> {
+            PolkadexOcex::markets()
+        }
+
+        fn custody_balances() -> Vec<(AssetId, Balance)> {
+            PolkadexOcex::custody_balances()
+        }
+
+        fn pending_withdrawals(
+            account: AccountId,
+        ) -> Vec<(u64, WithdrawalRequest<AccountId, Balance, BlockNumber>)> {
+            PolkadexOcex::pending_withdrawals_of(&account)
+        }
+
+        fn registered_enclaves() -> Vec<EnclaveStatus<AccountId>> {
+            PolkadexOcex::registered_enclaves()
+        }
+
+        fn paused_operations() -> Vec<(OcexOperation, Option<AssetId>)> {
+            PolkadexOcex::paused_operations()
+        }
+
+        fn trading_key_owner(trading_key: AccountId) -> Option<AccountId> {
+            PolkadexOcex::trading_key_owner(trading_key)
+        }
+
+        fn reserve_ratios() -> Vec<(AssetId, ReserveStatus<Balance>)> {
+            PolkadexOcex::reserve_ratios()
+        }
+    }
+
     #[cfg(feature = "runtime-benchmarks")]
     impl frame_benchmarking::Benchmark<Block> for Runtime {
         fn dispatch_benchmark(
@@ -670,7 +819,9 @@ impl_runtime_apis! {
             add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
             add_benchmark!(params, batches, pallet_balances, Balances);
             add_benchmark!(params, batches, pallet_timestamp, Timestamp);
-            add_benchmark!(params, batches, pallet_template, TemplateModule);
+            add_benchmark!(params, batches, polkadex_fungible_assets, PolkadexFungibleAsset);
+            add_benchmark!(params, batches, polkadex_orderbook, PolkadexOrderbook);
+            add_benchmark!(params, batches, polkadex_ocex, PolkadexOcex);
 
             if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
             Ok(batches)
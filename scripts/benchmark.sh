@@ -0,0 +1,22 @@
+#!/usr/bin/env bash
+# This script is meant to be run on Unix/Linux based systems
+set -e
+
+echo "*** Regenerating the weights of the Polkadex pallets ***"
+
+cd $(dirname ${BASH_SOURCE[0]})/..
+
+cargo build --release -p polkadex-node --features runtime-benchmarks
+
+for pallet in ocex:polkadex_ocex fungible-assets:polkadex_fungible_assets orderbook:polkadex_orderbook; do
+    ./target/release/polkadex-node benchmark \
+        --chain=dev \
+        --steps=50 \
+        --repeat=20 \
+        --pallet=${pallet#*:} \
+        --extrinsic='*' \
+        --execution=wasm \
+        --wasm-execution=compiled \
+        --heap-pages=4096 \
+        --output=./pallets/${pallet%%:*}/src/weights.rs
+done